@@ -4,7 +4,7 @@ use crate::{
 };
 use binary_agreement::BinaryAgreement;
 use core::fmt;
-use reliable_broadcast::ReliableBroadcast;
+use reliable_broadcast::{merkle::MerkleHasher, ReliableBroadcast};
 use std::collections::{BTreeMap, HashMap};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
@@ -14,8 +14,13 @@ pub trait AsynchronousCommonSubset: fmt::Debug {
     type NodeId: NodeId + 'static;
     type ValidatorIndex: ValidatorIndex + 'static;
     type SessionId: SessionId + 'static;
-    type ReliableBroadcast: ReliableBroadcast<NodeId = Self::NodeId, ValidatorIndex = Self::ValidatorIndex>
-        + Send
+    type Hasher: MerkleHasher;
+    type ReliableBroadcast: ReliableBroadcast<
+            NodeId = Self::NodeId,
+            ValidatorIndex = Self::ValidatorIndex,
+            SessionId = Self::SessionId,
+            Hasher = Self::Hasher,
+        > + Send
         + 'static;
     type BinaryAgreement: BinaryAgreement<
             NodeId = Self::NodeId,
@@ -33,6 +38,8 @@ pub trait AsynchronousCommonSubset: fmt::Debug {
 
     fn terminate_reliable_broadcast(&self, target_id: &Self::NodeId);
 
+    fn get_reliable_broadcast_session_id(&self, target_id: &Self::NodeId) -> Self::SessionId;
+
     fn create_binary_agreement_instance(
         &mut self,
         target_id: &Self::NodeId,
@@ -53,10 +60,9 @@ pub trait AsynchronousCommonSubset: fmt::Debug {
         validator_indices: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
         secret_key_share: SecretKeyShare,
         public_key_shares: PublicKeyShares,
-    ) -> Result<AsynchronousCommonSubsetState<Self::NodeId>> {
+    ) -> Result<AsynchronousCommonSubsetState<Self::NodeId, Self::SessionId, Self::Hasher>> {
         // initialize state
-        let state: Arc<Mutex<AsynchronousCommonSubsetState<Self::NodeId>>> =
-            Arc::new(Mutex::new(AsynchronousCommonSubsetState::new()));
+        let state = Arc::new(Mutex::new(AsynchronousCommonSubsetState::new()));
 
         let rb_validator_set =
             reliable_broadcast::validator::ValidatorSet::new(validator_indices.clone())?;
@@ -67,72 +73,64 @@ pub trait AsynchronousCommonSubset: fmt::Debug {
         let mut rb_threads = BTreeMap::new();
         let mut ba_receive_channels = HashMap::new();
         let mut ba_send_channels = HashMap::new();
+        let (rb_done_sender, rb_done_receiver) = mpsc::channel();
         for (node_id, _validator_index) in validator_indices {
             let (ba_input_sender, ba_input_receiver) = mpsc::channel();
             ba_receive_channels.insert(node_id.clone(), ba_input_receiver);
             ba_send_channels.insert(node_id.clone(), ba_input_sender.clone());
             let rb_instance = self.create_reliable_broadcast_instance(&node_id);
+            let rb_session_id = self.get_reliable_broadcast_session_id(&node_id);
             let validator_set = rb_validator_set.clone();
             let state_for_rb_thread = state.clone();
             let node_id_for_rb_thread = node_id.clone();
-            let rb_thread = if &node_id == self.my_id() {
-                let input = input.clone();
-                thread::spawn(move || {
-                    let rbc_out = rb_instance.propose(input, validator_set);
-                    let ba_input = match &rbc_out {
-                        Ok(rbc_out) => {
-                            if rbc_out.is_decided() {
-                                Some(true)
-                            } else {
-                                None
-                            }
-                        }
-                        Err(_) => {
-                            // TODO set fault logs (RBC failed)
-                            None
-                        }
-                    };
-                    let mut locked_state = state_for_rb_thread
-                        .lock()
-                        .expect("state mutex cannot be locked...");
-                    locked_state
-                        .set_binary_agreement_input(node_id_for_rb_thread.clone(), ba_input);
-                    if let Ok(rbc_out) = rbc_out {
-                        locked_state.set_reliable_broadcast_state(node_id_for_rb_thread, rbc_out);
-                    }
-                    ba_input_sender
-                        .send(ba_input)
-                        .expect("could not send binary agreement input message...");
-                })
+            let input = if &node_id == self.my_id() {
+                Some(input.clone())
             } else {
-                thread::spawn(move || {
-                    let rbc_out = rb_instance.execute(None, validator_set);
-                    let ba_input = match &rbc_out {
-                        Ok(rbc_out) => {
-                            if rbc_out.is_decided() {
-                                Some(true)
-                            } else {
-                                None
-                            }
-                        }
-                        Err(_) => {
-                            // TODO set fault logs (RBC failed)
+                None
+            };
+            let rb_done_sender = rb_done_sender.clone();
+            let rb_thread = thread::spawn(move || {
+                let rbc_out = match input {
+                    Some(input) => rb_instance.propose(input, validator_set, rb_session_id),
+                    None => rb_instance.execute(None, validator_set, rb_session_id),
+                };
+                let ba_input = match &rbc_out {
+                    Ok(rbc_out) => {
+                        if rbc_out.is_decided() {
+                            Some(true)
+                        } else {
                             None
                         }
-                    };
-                    let mut locked_state = state_for_rb_thread
-                        .lock()
-                        .expect("state mutex cannot be locked...");
-                    locked_state
-                        .set_binary_agreement_input(node_id_for_rb_thread.clone(), ba_input);
-                    if let Ok(rbc_out) = rbc_out {
-                        locked_state.set_reliable_broadcast_state(node_id_for_rb_thread, rbc_out);
                     }
-                    ba_input_sender
-                        .send(ba_input)
-                        .expect("could not send binary agreement input message...");
-                })
-            };
+                    Err(_) => {
+                        // TODO set fault logs (RBC failed)
+                        None
+                    }
+                };
+                state_for_rb_thread
+                    .lock()
+                    .expect("state mutex cannot be locked...")
+                    .set_binary_agreement_input(node_id_for_rb_thread.clone(), ba_input);
+                ba_input_sender
+                    .send(ba_input)
+                    .expect("could not send binary agreement input message...");
+                rb_done_sender
+                    .send(())
+                    .expect("could not send reliable broadcast done message...");
+                // keep answering the shard requests of nodes which are late to deliver until terminated
+                let rbc_out = rbc_out.and_then(|mut rbc_out| {
+                    if rbc_out.is_decided() {
+                        rb_instance.serve_shard_requests(&mut rbc_out)?;
+                    }
+                    Ok(rbc_out)
+                });
+                if let Ok(rbc_out) = rbc_out {
+                    state_for_rb_thread
+                        .lock()
+                        .expect("state mutex cannot be locked...")
+                        .set_reliable_broadcast_state(node_id_for_rb_thread, rbc_out);
+                }
+            });
             rb_threads.insert(node_id, rb_thread);
         }
 
@@ -194,12 +192,27 @@ pub trait AsynchronousCommonSubset: fmt::Debug {
             let _ = ba_thread.join().unwrap();
         }
         // terminate unfinished RB process
-        {
-            let locked_state = state.lock().expect("state mutex cannot be locked...");
-            for (node_id, ba_out) in locked_state.as_binary_agreement_outputs() {
-                if !ba_out.unwrap_or(false) {
-                    self.terminate_reliable_broadcast(node_id);
-                }
+        let ba_outputs = state
+            .lock()
+            .expect("state mutex cannot be locked...")
+            .as_binary_agreement_outputs()
+            .clone();
+        let is_accepted = |node_id: &Self::NodeId| ba_outputs.get(node_id) == Some(&Some(true));
+        for node_id in rb_threads.keys() {
+            if !is_accepted(node_id) {
+                self.terminate_reliable_broadcast(node_id);
+            }
+        }
+        // wait for the RB processes of the accepted values to deliver, then stop them answering
+        // shard requests.
+        for _ in 0..rb_threads.len() {
+            rb_done_receiver
+                .recv()
+                .expect("could not receive reliable broadcast done message...");
+        }
+        for node_id in rb_threads.keys() {
+            if is_accepted(node_id) {
+                self.terminate_reliable_broadcast(node_id);
             }
         }
         for (_node_id, rb_thread) in rb_threads {
@@ -1,18 +1,19 @@
-use crate::node::NodeId;
+use crate::{node::NodeId, session::SessionId};
 use binary_agreement::BinaryAgreementState;
+use reliable_broadcast::merkle::{MerkleHasher, Sha3Hasher};
 use reliable_broadcast::ReliableBroadcastState;
 use std::collections::BTreeMap;
 
 #[derive(Debug, Clone)]
-pub struct AsynchronousCommonSubsetState<ID: NodeId> {
+pub struct AsynchronousCommonSubsetState<ID: NodeId, SID: SessionId, H: MerkleHasher = Sha3Hasher> {
     reliable_broadcast_outputs: BTreeMap<ID, Option<Vec<u8>>>,
-    reliable_broadcast_fault_logs: BTreeMap<ID, Vec<reliable_broadcast::FaultLog<ID>>>,
+    reliable_broadcast_fault_logs: BTreeMap<ID, Vec<reliable_broadcast::FaultLog<ID, SID, H>>>,
     binary_agreement_inputs: BTreeMap<ID, Option<bool>>,
     binary_agreement_outputs: BTreeMap<ID, Option<bool>>,
     binary_agreement_fault_logs: BTreeMap<ID, Vec<binary_agreement::FaultLog<ID>>>,
 }
 
-impl<ID: NodeId> AsynchronousCommonSubsetState<ID> {
+impl<ID: NodeId, SID: SessionId, H: MerkleHasher> AsynchronousCommonSubsetState<ID, SID, H> {
     pub(crate) fn new() -> Self {
         Self {
             reliable_broadcast_outputs: BTreeMap::default(),
@@ -36,7 +37,7 @@ impl<ID: NodeId> AsynchronousCommonSubsetState<ID> {
     >(
         &mut self,
         node_id: ID,
-        state: ReliableBroadcastState<ID, IDX>,
+        state: ReliableBroadcastState<ID, IDX, SID, H>,
     ) {
         let (output, fault_logs) = state.into_output_and_logs();
         self.set_reliable_broadcast_output(node_id.clone(), output);
@@ -49,14 +50,14 @@ impl<ID: NodeId> AsynchronousCommonSubsetState<ID> {
 
     pub fn as_reliable_broadcast_fault_logs(
         &self,
-    ) -> &BTreeMap<ID, Vec<reliable_broadcast::FaultLog<ID>>> {
+    ) -> &BTreeMap<ID, Vec<reliable_broadcast::FaultLog<ID, SID, H>>> {
         &self.reliable_broadcast_fault_logs
     }
 
     fn set_reliable_broadcast_fault_logs(
         &mut self,
         node_id: ID,
-        fault_logs: Vec<reliable_broadcast::FaultLog<ID>>,
+        fault_logs: Vec<reliable_broadcast::FaultLog<ID, SID, H>>,
     ) {
         self.reliable_broadcast_fault_logs
             .insert(node_id, fault_logs);
@@ -76,11 +77,11 @@ impl<ID: NodeId> AsynchronousCommonSubsetState<ID> {
 
     pub(crate) fn set_binary_agreement_state<
         IDX: binary_agreement::validator::ValidatorIndex,
-        SID: binary_agreement::session::SessionId,
+        BSID: binary_agreement::session::SessionId,
     >(
         &mut self,
         node_id: ID,
-        state: BinaryAgreementState<ID, IDX, SID>,
+        state: BinaryAgreementState<ID, IDX, BSID>,
     ) {
         let (output, fault_logs) = state.into_output_and_logs();
         self.set_binary_agreement_output(node_id.clone(), output);
@@ -8,7 +8,10 @@ use binary_agreement::{
 use logger::prelude::*;
 use rand::thread_rng;
 use reliable_broadcast::{
-    message::BroadcastMessage, node::NodeMessage as RbcNodeMessage, ReliableBroadcast,
+    merkle::Sha3Hasher,
+    message::{BroadcastMessage, BroadcastMessageContent},
+    node::NodeMessage as RbcNodeMessage,
+    ReliableBroadcast,
 };
 use std::collections::{BTreeMap, VecDeque};
 use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
@@ -62,8 +65,8 @@ type SessionId = String;
 struct ReliableBroadcastImpl {
     id: NodeId,
     target_id: NodeId,
-    message_receiver: Arc<Mutex<Receiver<RbcNodeMessage<NodeId>>>>,
-    message_router: BTreeMap<NodeId, SyncSender<RbcNodeMessage<NodeId>>>,
+    message_receiver: Arc<Mutex<Receiver<RbcNodeMessage<NodeId, SessionId>>>>,
+    message_router: BTreeMap<NodeId, SyncSender<RbcNodeMessage<NodeId, SessionId>>>,
 }
 
 impl fmt::Debug for ReliableBroadcastImpl {
@@ -75,12 +78,14 @@ impl fmt::Debug for ReliableBroadcastImpl {
 impl ReliableBroadcast for ReliableBroadcastImpl {
     type NodeId = NodeId;
     type ValidatorIndex = Index;
+    type SessionId = SessionId;
+    type Hasher = Sha3Hasher;
 
     fn my_id(&self) -> &NodeId {
         &self.id
     }
 
-    fn next_message(&self) -> RbcNodeMessage<NodeId> {
+    fn next_message(&self) -> RbcNodeMessage<NodeId, SessionId> {
         let receiver = self.message_receiver.lock().unwrap();
         let message = receiver.recv().unwrap();
         let sender_id = match &message {
@@ -94,12 +99,18 @@ impl ReliableBroadcast for ReliableBroadcastImpl {
         message
     }
 
-    fn send_message(&self, target_id: NodeId, message: BroadcastMessage) {
+    fn send_message(&self, target_id: NodeId, message: BroadcastMessage<SessionId>) {
         assert_ne!(self.id, target_id);
-        let message_type = match message {
-            BroadcastMessage::Value(_) => "value message",
-            BroadcastMessage::Echo(_) => "echo message",
-            BroadcastMessage::Ready(_) => "ready message",
+        let message_type = match message.content {
+            BroadcastMessageContent::Value(_) => "value message",
+            BroadcastMessageContent::Echo(_) => "echo message",
+            BroadcastMessageContent::Ready(_) => "ready message",
+            BroadcastMessageContent::PlainValue(_) => "plain value message",
+            BroadcastMessageContent::PlainEcho(_) => "plain echo message",
+            BroadcastMessageContent::EchoHash(_) => "echo hash message",
+            BroadcastMessageContent::CanDecode(_) => "can decode message",
+            BroadcastMessageContent::ShardRequest(_) => "shard request message",
+            BroadcastMessageContent::ShardResponse(_) => "shard response message",
         };
         debug!(
             "[send message]{:?} -> {}-{}: {}",
@@ -121,6 +132,7 @@ struct BinaryAgreementImpl {
     message_receiver: Arc<Mutex<Receiver<BaNodeMessage<NodeId>>>>,
     message_router: BTreeMap<NodeId, SyncSender<BaNodeMessage<NodeId>>>,
     message_queue: BTreeMap<Epoch, VecDeque<BaNodeMessage<NodeId>>>,
+    is_muted: bool,
 }
 
 impl fmt::Debug for BinaryAgreementImpl {
@@ -174,11 +186,16 @@ impl BinaryAgreement for BinaryAgreementImpl {
     }
 
     fn send_message(&self, target_id: Self::NodeId, message: BinaryAgreementMessage) {
+        if self.is_muted {
+            return;
+        }
         let message_type = match message.content {
             BinaryAgreementMessageContent::BVal(_) => "BVal",
             BinaryAgreementMessageContent::Aux(_) => "AUX",
             BinaryAgreementMessageContent::Conf(_) => "CONF",
             BinaryAgreementMessageContent::Coin(_) => "COIN",
+            BinaryAgreementMessageContent::Prop(_) => "PROP",
+            BinaryAgreementMessageContent::PropAux(_) => "PROP_AUX",
         };
         debug!(
             "[send message]{} -> {}: {:?} {}",
@@ -201,10 +218,14 @@ impl BinaryAgreement for BinaryAgreementImpl {
 struct TestNode {
     id: NodeId,
     index: Index,
-    rbc_message_receivers: BTreeMap<NodeId, Arc<Mutex<Receiver<RbcNodeMessage<NodeId>>>>>,
-    rbc_message_router: BTreeMap<NodeId, BTreeMap<NodeId, SyncSender<RbcNodeMessage<NodeId>>>>,
+    rbc_message_receivers:
+        BTreeMap<NodeId, Arc<Mutex<Receiver<RbcNodeMessage<NodeId, SessionId>>>>>,
+    rbc_message_router:
+        BTreeMap<NodeId, BTreeMap<NodeId, SyncSender<RbcNodeMessage<NodeId, SessionId>>>>,
     ba_message_receivers: BTreeMap<NodeId, Arc<Mutex<Receiver<BaNodeMessage<NodeId>>>>>,
     ba_message_router: BTreeMap<NodeId, BTreeMap<NodeId, SyncSender<BaNodeMessage<NodeId>>>>,
+    /// whether the binary agreement messages of this node are dropped
+    is_ba_muted: bool,
 }
 
 impl fmt::Debug for TestNode {
@@ -217,6 +238,7 @@ impl<'a> AsynchronousCommonSubset for TestNode {
     type NodeId = NodeId;
     type ValidatorIndex = Index;
     type SessionId = SessionId;
+    type Hasher = Sha3Hasher;
     type ReliableBroadcast = ReliableBroadcastImpl;
     type BinaryAgreement = BinaryAgreementImpl;
 
@@ -259,6 +281,10 @@ impl<'a> AsynchronousCommonSubset for TestNode {
             .expect("rbc terminate message should be sent without error...");
     }
 
+    fn get_reliable_broadcast_session_id(&self, target_id: &NodeId) -> Self::SessionId {
+        format!("test-rbc-{}", target_id)
+    }
+
     fn create_binary_agreement_instance(&mut self, target_id: &NodeId) -> Self::BinaryAgreement {
         debug!("BA instance created: {}-{}", self.id, target_id);
         let message_receiver = self.ba_message_receivers.get(target_id).unwrap().clone();
@@ -276,6 +302,7 @@ impl<'a> AsynchronousCommonSubset for TestNode {
             message_receiver,
             message_router,
             message_queue: BTreeMap::new(),
+            is_muted: self.is_ba_muted,
         }
     }
 
@@ -289,14 +316,7 @@ fn gen_random_secret_key_shares(threshold: usize) -> SecretKeyShares {
     SecretKeyShares::random(threshold, &mut rnd)
 }
 
-#[test]
-fn test_simple_procedure() {
-    // init logger
-    let mut builder = logger::default::DefaultLoggerBuilder::new();
-    builder.is_async(true);
-    builder.level(logger::Level::Debug);
-    let _logger = builder.build();
-
+fn init_network(size: NodeId) -> BTreeMap<NodeId, TestNode> {
     let channel_size = 10000;
     // { acs_node_id: { target_index: (rbc_receiver, ba_receiver) } }
     let mut message_receivers: BTreeMap<
@@ -304,7 +324,7 @@ fn test_simple_procedure() {
         BTreeMap<
             NodeId,
             (
-                Arc<Mutex<Receiver<RbcNodeMessage<NodeId>>>>,
+                Arc<Mutex<Receiver<RbcNodeMessage<NodeId, SessionId>>>>,
                 Arc<Mutex<Receiver<BaNodeMessage<NodeId>>>>,
             ),
         >,
@@ -312,18 +332,18 @@ fn test_simple_procedure() {
     // { acs_index: { send_target_node_id: sender } }
     let mut rbc_message_router: BTreeMap<
         NodeId,
-        BTreeMap<NodeId, SyncSender<RbcNodeMessage<NodeId>>>,
+        BTreeMap<NodeId, SyncSender<RbcNodeMessage<NodeId, SessionId>>>,
     > = BTreeMap::new();
     // { acs_index: { send_target_node_id: sender } }
     let mut ba_message_router: BTreeMap<
         NodeId,
         BTreeMap<NodeId, SyncSender<BaNodeMessage<NodeId>>>,
     > = BTreeMap::new();
-    for id in 1..=4 {
+    for id in 1..=size {
         message_receivers.insert(id, BTreeMap::default());
         rbc_message_router.insert(id, BTreeMap::default());
         ba_message_router.insert(id, BTreeMap::default());
-        for child_id in 1..=4 {
+        for child_id in 1..=size {
             let (rb_sender, rb_receiver) = sync_channel(channel_size);
             let (ba_sender, ba_receiver) = sync_channel(channel_size);
             message_receivers.get_mut(&id).unwrap().insert(
@@ -361,9 +381,22 @@ fn test_simple_procedure() {
                 rbc_message_router: rbc_message_router.clone(),
                 ba_message_receivers,
                 ba_message_router: ba_message_router.clone(),
+                is_ba_muted: false,
             },
         );
     }
+    nodes
+}
+
+#[test]
+fn test_simple_procedure() {
+    // init logger
+    let mut builder = logger::default::DefaultLoggerBuilder::new();
+    builder.is_async(true);
+    builder.level(logger::Level::Debug);
+    let _logger = builder.build();
+
+    let nodes = init_network(4);
 
     let inputs: Vec<&str> = vec!["Foo1", "Foo2", "Foo3", "Foo4"];
     let mut handles = BTreeMap::new();
@@ -416,3 +449,79 @@ fn test_simple_procedure() {
         }
     }
 }
+
+#[test]
+fn test_late_node_retrieves_shards() {
+    let mut nodes = init_network(7);
+    // the binary agreements can't complete without the late node, since nodes 5 and 6 never
+    // take part in them.
+    for id in 5..=6 {
+        nodes.get_mut(&id).unwrap().is_ba_muted = true;
+    }
+    let mut late_node = nodes.remove(&7).unwrap();
+
+    let inputs: Vec<&str> = vec!["Foo1", "Foo2", "Foo3", "Foo4", "Foo5", "Foo6", "Foo7"];
+    let mut validator_indices: BTreeMap<NodeId, Index> =
+        nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+    validator_indices.insert(late_node.id, late_node.index);
+    let secret_key_shares = gen_random_secret_key_shares(2);
+    let public_key_shares = secret_key_shares.public_keys();
+    let mut handles = BTreeMap::new();
+    for (id, mut node) in nodes {
+        let input = inputs[(id - 1) as usize].as_bytes().to_vec();
+        let validator_indices = validator_indices.clone();
+        let secret_key_share = secret_key_shares.secret_key_share(*node.index.as_ref());
+        let public_key_shares = public_key_shares.clone();
+        let handle = thread::spawn(move || {
+            node.propose(
+                input,
+                validator_indices,
+                secret_key_share,
+                public_key_shares,
+            )
+        });
+        handles.insert(id, handle);
+    }
+
+    // the late node misses every `Value` and `Echo` message of the values the others deliver.
+    for target_id in 1..=6 {
+        let receiver = late_node.rbc_message_receivers[&target_id].clone();
+        let mut ready_messages = Vec::new();
+        while ready_messages.len() < 6 {
+            let node_message = receiver.lock().unwrap().recv().unwrap();
+            if let RbcNodeMessage::BroadcastMessage { message, .. } = &node_message {
+                if let BroadcastMessageContent::Ready(_) = message.content {
+                    ready_messages.push(node_message);
+                }
+            }
+        }
+        let sender = &late_node.rbc_message_router[&late_node.id][&target_id];
+        for node_message in ready_messages {
+            sender.send(node_message).unwrap();
+        }
+    }
+    let input = inputs[(late_node.id - 1) as usize].as_bytes().to_vec();
+    let secret_key_share = secret_key_shares.secret_key_share(*late_node.index.as_ref());
+    let late_state = late_node
+        .propose(
+            input,
+            validator_indices,
+            secret_key_share,
+            public_key_shares,
+        )
+        .unwrap();
+    let late_outputs = late_state.into_output();
+    for target_id in 1..=6 {
+        assert_eq!(
+            Some(inputs[(target_id - 1) as usize].as_bytes()),
+            late_outputs[&target_id].as_deref()
+        );
+    }
+    for (id, handle) in handles {
+        let state = handle
+            .join()
+            .unwrap()
+            .unwrap_or_else(|err| panic!("id: {id:?}, err: {err:?}"));
+        assert_eq!(late_outputs, state.into_output());
+    }
+}
@@ -34,6 +34,10 @@ impl BinaryValueSet {
         self.0.is_none()
     }
 
+    pub fn get(&self) -> Option<BinaryValues> {
+        self.0
+    }
+
     pub fn values(&self) -> &BinaryValues {
         self.0.as_ref().unwrap()
     }
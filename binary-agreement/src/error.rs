@@ -1,3 +1,4 @@
+use crate::{epoch::Epoch, state::RoundDiagnostics};
 use thiserror::Error as ThisError;
 
 #[derive(ThisError, Debug)]
@@ -11,8 +12,26 @@ pub enum Error {
     #[error("Threshold Encryption Error: {cause:?}")]
     ThresholdCryptError { cause: threshold_crypto::Error },
 
-    #[error("Invalid combined signature hash")]
-    InvalidCombinedSignature,
+    #[error("Input value true requires a validity proof")]
+    MissingValidityProof,
+
+    #[error("Input validity proof does not satisfy the predicate")]
+    InvalidValidityProof,
+
+    #[error("Both values have been proposed in {epoch:?}, more than f nodes are faulty")]
+    ConflictingPropValues { epoch: Epoch },
+
+    #[error("No decision within {limit} rounds: {diagnostics:?}")]
+    RoundLimitExceeded {
+        limit: u64,
+        diagnostics: Box<RoundDiagnostics>,
+    },
+
+    #[error("Round not completed within {limit} messages: {diagnostics:?}")]
+    RoundMessageLimitExceeded {
+        limit: usize,
+        diagnostics: Box<RoundDiagnostics>,
+    },
 }
 
 impl From<threshold_crypto::Error> for Error {
@@ -9,6 +9,8 @@ pub mod message;
 pub mod node;
 pub mod session;
 pub mod validator;
+pub mod validity;
+pub mod variant;
 
 mod state;
 pub use state::*;
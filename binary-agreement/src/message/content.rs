@@ -7,15 +7,30 @@ pub enum BinaryAgreementMessageContent {
     Aux(AuxMessage),
     Conf(ConfMessage),
     Coin(CommonCoinMessage),
+    Prop(PropMessage),
+    PropAux(PropAuxMessage),
 }
 
+/// `BVal(value)`, carrying the external validity proof of `true` in a validated agreement.
 #[derive(Clone, Debug, PartialEq)]
-pub struct BValMessage(bool);
+pub struct BValMessage(bool, Option<Vec<u8>>);
 
 impl BValMessage {
+    pub fn with_proof(value: bool, proof: Option<Vec<u8>>) -> Self {
+        Self(value, proof)
+    }
+
     pub fn into_inner(self) -> bool {
         self.0
     }
+
+    pub fn proof(&self) -> Option<&Vec<u8>> {
+        self.1.as_ref()
+    }
+
+    pub fn into_parts(self) -> (bool, Option<Vec<u8>>) {
+        (self.0, self.1)
+    }
 }
 
 impl AsRef<bool> for BValMessage {
@@ -26,17 +41,30 @@ impl AsRef<bool> for BValMessage {
 
 impl From<bool> for BValMessage {
     fn from(value: bool) -> Self {
-        Self(value)
+        Self(value, None)
     }
 }
 
+/// `Aux(value)`, carrying the external validity proof of `true` in a validated agreement.
 #[derive(Clone, Debug, PartialEq)]
-pub struct AuxMessage(bool);
+pub struct AuxMessage(bool, Option<Vec<u8>>);
 
 impl AuxMessage {
+    pub fn with_proof(value: bool, proof: Option<Vec<u8>>) -> Self {
+        Self(value, proof)
+    }
+
     pub fn into_inner(self) -> bool {
         self.0
     }
+
+    pub fn proof(&self) -> Option<&Vec<u8>> {
+        self.1.as_ref()
+    }
+
+    pub fn into_parts(self) -> (bool, Option<Vec<u8>>) {
+        (self.0, self.1)
+    }
 }
 
 impl AsRef<bool> for AuxMessage {
@@ -47,7 +75,7 @@ impl AsRef<bool> for AuxMessage {
 
 impl From<bool> for AuxMessage {
     fn from(value: bool) -> Self {
-        Self(value)
+        Self(value, None)
     }
 }
 
@@ -92,3 +120,47 @@ impl From<SignatureShare> for CommonCoinMessage {
         Self(value)
     }
 }
+
+/// `Prop(value)` of the signature-free variant, where `None` stands for ⊥.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropMessage(Option<bool>);
+
+impl PropMessage {
+    pub fn into_inner(self) -> Option<bool> {
+        self.0
+    }
+}
+
+impl AsRef<Option<bool>> for PropMessage {
+    fn as_ref(&self) -> &Option<bool> {
+        &self.0
+    }
+}
+
+impl From<Option<bool>> for PropMessage {
+    fn from(value: Option<bool>) -> Self {
+        Self(value)
+    }
+}
+
+/// `PropAux(value)` of the signature-free variant, where `None` stands for ⊥.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropAuxMessage(Option<bool>);
+
+impl PropAuxMessage {
+    pub fn into_inner(self) -> Option<bool> {
+        self.0
+    }
+}
+
+impl AsRef<Option<bool>> for PropAuxMessage {
+    fn as_ref(&self) -> &Option<bool> {
+        &self.0
+    }
+}
+
+impl From<Option<bool>> for PropAuxMessage {
+    fn from(value: Option<bool>) -> Self {
+        Self(value)
+    }
+}
@@ -7,6 +7,8 @@ use crate::{
     session::SessionId,
     state::{BinaryAgreementState, FaultLog, FaultType},
     validator::{ValidatorIndex, ValidatorKeyShares, ValidatorSet},
+    validity::ExternalValidity,
+    variant::BinaryAgreementVariant,
     Error, Result,
 };
 use core::fmt;
@@ -26,6 +28,27 @@ pub trait BinaryAgreement: fmt::Debug {
         println!("{:?} has just detected terminate message.", self);
     }
 
+    /// protocol run by `propose` and `propose_validated`
+    fn variant(&self) -> BinaryAgreementVariant {
+        BinaryAgreementVariant::ThresholdCoin
+    }
+
+    /// maximum number of rounds to run without deciding, unbounded by default
+    fn max_rounds(&self) -> Option<u64> {
+        None
+    }
+
+    /// maximum number of messages to handle in a round without completing it, unbounded by default.
+    /// Unlike `max_rounds`, it also catches a round that never completes.
+    fn max_round_messages(&self) -> Option<usize> {
+        None
+    }
+
+    /// coin tossed locally by the signature-free variant when no value could be agreed in a round
+    fn local_coin(&mut self) -> bool {
+        rand::random()
+    }
+
     /// start binary agreement procedure
     fn propose(
         &mut self,
@@ -35,6 +58,58 @@ pub trait BinaryAgreement: fmt::Debug {
         session_id: Self::SessionId,
     ) -> Result<BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>> {
         let mut state = BinaryAgreementState::new(validator_set, validator_key_shares, session_id);
+        state.set_variant(self.variant());
+        self.execute(input, state)
+    }
+
+    /// start signature-free binary agreement procedure, which needs no key shares.
+    fn propose_signature_free(
+        &mut self,
+        input: bool,
+        validator_set: ValidatorSet<Self::NodeId, Self::ValidatorIndex>,
+        session_id: Self::SessionId,
+    ) -> Result<BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>> {
+        let state = BinaryAgreementState::new_signature_free(validator_set, session_id);
+        self.execute(input, state)
+    }
+
+    /// start validated binary agreement procedure.
+    /// `true` can only be decided if a proof satisfying `validate` exists, so an input of `true`
+    /// must come with such a proof.
+    fn propose_validated<F>(
+        &mut self,
+        input: bool,
+        proof: Option<Vec<u8>>,
+        validate: F,
+        validator_set: ValidatorSet<Self::NodeId, Self::ValidatorIndex>,
+        validator_key_shares: ValidatorKeyShares,
+        session_id: Self::SessionId,
+    ) -> Result<BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>>
+    where
+        F: Fn(&[u8]) -> bool + Send + 'static,
+    {
+        let mut validity = ExternalValidity::new(validate);
+        if let Some(proof) = proof {
+            if !validity.validate(&proof) {
+                return Err(Error::InvalidValidityProof);
+            }
+            let _ = validity.try_set_proof(proof);
+        }
+        if input && validity.proof().is_none() {
+            return Err(Error::MissingValidityProof);
+        }
+        let mut state = BinaryAgreementState::new(validator_set, validator_key_shares, session_id);
+        state.set_variant(self.variant());
+        state.set_external_validity(validity);
+        self.execute(input, state)
+    }
+
+    /// execute binary agreement procedure
+    fn execute(
+        &mut self,
+        input: bool,
+        mut state: BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>,
+    ) -> Result<BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>> {
         self.on_start_new_epoch(input, &mut state)?;
         loop {
             let node_message = self.next_message(state.epoch());
@@ -47,6 +122,15 @@ pub trait BinaryAgreement: fmt::Debug {
                     sender_id,
                     message: BinaryAgreementMessage { epoch, content },
                 } => {
+                    if let Some(limit) = self.max_round_messages() {
+                        if state.received_messages() >= limit {
+                            return Err(Error::RoundMessageLimitExceeded {
+                                limit,
+                                diagnostics: Box::new(state.round_diagnostics()),
+                            });
+                        }
+                    }
+                    state.count_received_message();
                     if !state.validator_set().contains(&sender_id) {
                         state.push_fault_log(FaultLog {
                             sender_id: sender_id.clone(),
@@ -66,19 +150,44 @@ pub trait BinaryAgreement: fmt::Debug {
                         });
                         continue;
                     }
-                    match content {
-                        BinaryAgreementMessageContent::BVal(message) => {
+                    match (state.variant(), content) {
+                        (_, BinaryAgreementMessageContent::BVal(message)) => {
                             self.handle_bval(&sender_id, epoch, message, &mut state)?;
                         }
-                        BinaryAgreementMessageContent::Aux(message) => {
+                        (_, BinaryAgreementMessageContent::Aux(message)) => {
                             self.handle_aux(&sender_id, epoch, message, &mut state)?;
                         }
-                        BinaryAgreementMessageContent::Conf(message) => {
+                        (
+                            BinaryAgreementVariant::ThresholdCoin,
+                            BinaryAgreementMessageContent::Conf(message),
+                        ) => {
                             self.handle_conf(&sender_id, epoch, message, &mut state)?;
                         }
-                        BinaryAgreementMessageContent::Coin(message) => {
+                        (
+                            BinaryAgreementVariant::ThresholdCoin,
+                            BinaryAgreementMessageContent::Coin(message),
+                        ) => {
                             self.handle_coin(&sender_id, epoch, message, &mut state)?;
                         }
+                        (
+                            BinaryAgreementVariant::SignatureFree,
+                            BinaryAgreementMessageContent::Prop(message),
+                        ) => {
+                            self.handle_prop(&sender_id, epoch, message, &mut state)?;
+                        }
+                        (
+                            BinaryAgreementVariant::SignatureFree,
+                            BinaryAgreementMessageContent::PropAux(message),
+                        ) => {
+                            self.handle_prop_aux(&sender_id, epoch, message, &mut state)?;
+                        }
+                        (_, content) => {
+                            state.push_fault_log(FaultLog {
+                                sender_id: sender_id.clone(),
+                                message: BinaryAgreementMessage { epoch, content },
+                                fault_type: FaultType::UnexpectedMessage,
+                            });
+                        }
                     }
                 }
             }
@@ -90,15 +199,30 @@ pub trait BinaryAgreement: fmt::Debug {
                         break;
                     } else {
                         // update epoch & start next round with
-                        state.increment_epoch();
-                        self.on_next_epoch(state.epoch());
-                        self.on_start_new_epoch(single_conf_value, &mut state)?;
+                        self.start_next_epoch(single_conf_value, &mut state)?;
                     }
                 } else {
                     // update epoch & start next round with
-                    state.increment_epoch();
-                    self.on_next_epoch(state.epoch());
-                    self.on_start_new_epoch(coin_output, &mut state)?;
+                    self.start_next_epoch(coin_output, &mut state)?;
+                }
+            }
+            if let Some(prop_aux_output) = state.get_prop_aux_output() {
+                let values = prop_aux_output.clone();
+                // the output holds at most one value besides ⊥.
+                match values.iter().find_map(|value| *value) {
+                    Some(value) if values.len() == 1 => {
+                        state.set_output(value);
+                        break;
+                    }
+                    Some(value) => {
+                        // update epoch & start next round with
+                        self.start_next_epoch(value, &mut state)?;
+                    }
+                    None => {
+                        // update epoch & start next round with
+                        let coin_output = self.local_coin();
+                        self.start_next_epoch(coin_output, &mut state)?;
+                    }
                 }
             }
         }
@@ -113,11 +237,34 @@ pub trait BinaryAgreement: fmt::Debug {
         state.set_estimated(estimate);
         // broadcast BVal message
         let _ = state.try_add_sent_bval(estimate);
-        self.broadcast_bval_message(estimate, *state.epoch(), state.validators().clone())?;
+        self.broadcast_bval_message(
+            state.bval_message(estimate),
+            *state.epoch(),
+            state.validators().clone(),
+        )?;
         let _ = state.try_add_received_bval(estimate, self.my_id().clone());
         Ok(())
     }
 
+    fn start_next_epoch(
+        &mut self,
+        estimate: bool,
+        state: &mut BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>,
+    ) -> Result<()> {
+        if let Some(limit) = self.max_rounds() {
+            // the current round is the last one allowed
+            if state.epoch().to_u64() + 1 >= limit {
+                return Err(Error::RoundLimitExceeded {
+                    limit,
+                    diagnostics: Box::new(state.round_diagnostics()),
+                });
+            }
+        }
+        state.increment_epoch();
+        self.on_next_epoch(state.epoch());
+        self.on_start_new_epoch(estimate, state)
+    }
+
     fn handle_bval(
         &self,
         sender_id: &Self::NodeId,
@@ -125,6 +272,22 @@ pub trait BinaryAgreement: fmt::Debug {
         message: BValMessage,
         state: &mut BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>,
     ) -> Result<()> {
+        if !state.accept_validity_proof(*message.as_ref(), message.proof()) {
+            let fault_type = if message.proof().is_some() {
+                FaultType::InvalidValidityProof
+            } else {
+                FaultType::MissingValidityProof
+            };
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BinaryAgreementMessage {
+                    epoch,
+                    content: BinaryAgreementMessageContent::BVal(message),
+                },
+                fault_type,
+            });
+            return Ok(());
+        }
         let value = message.into_inner();
         if !state.try_add_received_bval(value, sender_id.clone()) {
             state.push_fault_log(FaultLog {
@@ -144,16 +307,26 @@ pub trait BinaryAgreement: fmt::Debug {
         // multicast BVal(value).
         if count >= max_durable_faulty_size + 1 {
             if state.try_add_sent_bval(value) {
-                self.broadcast_bval_message(value, epoch, state.validators().clone())?;
+                self.broadcast_bval_message(
+                    state.bval_message(value),
+                    epoch,
+                    state.validators().clone(),
+                )?;
             }
         }
 
         // upon receiving BVal(value) messages from 2f + 1 nodes, update bin_values.
         if count >= 2 * max_durable_faulty_size + 1 {
-            if state.try_update_bin_values(value) {
+            let is_first_bin_value = state.get_bin_values().is_not_set();
+            // the signature-free variant counts Aux as one vote per node, so Aux is multicast only
+            // for the first value of bin_values there.
+            if state.try_update_bin_values(value)
+                && (is_first_bin_value || state.variant() == BinaryAgreementVariant::ThresholdCoin)
+            {
                 // multicast Aux(value)
-                self.broadcast_aux_message(value, epoch, state.validators().clone())?;
-                self.handle_aux(self.my_id(), epoch, AuxMessage::from(value), state)?;
+                let aux_message = state.aux_message(value);
+                self.broadcast_aux_message(aux_message.clone(), epoch, state.validators().clone())?;
+                self.handle_aux(self.my_id(), epoch, aux_message, state)?;
             }
         }
 
@@ -167,24 +340,70 @@ pub trait BinaryAgreement: fmt::Debug {
         message: AuxMessage,
         state: &mut BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>,
     ) -> Result<()> {
+        if !state.accept_validity_proof(*message.as_ref(), message.proof()) {
+            let fault_type = if message.proof().is_some() {
+                FaultType::InvalidValidityProof
+            } else {
+                FaultType::MissingValidityProof
+            };
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BinaryAgreementMessage {
+                    epoch,
+                    content: BinaryAgreementMessageContent::Aux(message),
+                },
+                fault_type,
+            });
+            return Ok(());
+        }
         let value = message.into_inner();
-        if !state.try_add_received_aux(value, sender_id.clone()) {
+        // a node votes for a single value with Aux in the signature-free variant.
+        let fault_type = if state.variant() == BinaryAgreementVariant::SignatureFree
+            && state.has_received_aux(!value, sender_id)
+        {
+            Some(FaultType::ConflictingValue)
+        } else if !state.try_add_received_aux(value, sender_id.clone()) {
+            Some(FaultType::DuplicateAux)
+        } else {
+            None
+        };
+        if let Some(fault_type) = fault_type {
             state.push_fault_log(FaultLog {
                 sender_id: sender_id.clone(),
                 message: BinaryAgreementMessage {
                     epoch,
                     content: BinaryAgreementMessageContent::Aux(value.into()),
                 },
-                fault_type: FaultType::DuplicateAux,
+                fault_type,
             });
             return Ok(());
         }
         if self.try_set_aux_output(state)? {
-            // start conf phase by multicast Conf(aux output values)
             let aux_output = state.get_aux_output();
             let values = *aux_output.values();
-            self.broadcast_conf_message(values, *state.epoch(), state.validators().clone())?;
-            self.handle_conf(self.my_id(), epoch, ConfMessage::from(values), state)?;
+            match state.variant() {
+                BinaryAgreementVariant::ThresholdCoin => {
+                    // start conf phase by multicast Conf(aux output values)
+                    self.broadcast_conf_message(
+                        values,
+                        *state.epoch(),
+                        state.validators().clone(),
+                    )?;
+                    self.handle_conf(self.my_id(), epoch, ConfMessage::from(values), state)?;
+                }
+                BinaryAgreementVariant::SignatureFree => {
+                    // start prop phase by multicast Prop(v) if aux output values are {v} and Aux(v)
+                    // comes from a strict majority of the nodes, Prop(⊥) otherwise. Since every
+                    // node votes once with Aux, correct nodes never propose different values.
+                    let size = state.validator_set().size();
+                    let value = values
+                        .single()
+                        .filter(|value| 2 * state.get_received_aux_count(*value) > size);
+                    let _ = state.try_add_sent_prop(value);
+                    self.broadcast_prop_message(value, *state.epoch(), state.validators().clone())?;
+                    self.handle_prop(self.my_id(), epoch, PropMessage::from(value), state)?;
+                }
+            }
         }
         Ok(())
     }
@@ -265,54 +484,169 @@ pub trait BinaryAgreement: fmt::Debug {
         message: CommonCoinMessage,
         state: &mut BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>,
     ) -> Result<()> {
-        let signature_share = message.into_inner();
-        // check message signature is valid
+        if state.is_coin_decided() {
+            return Ok(());
+        }
+        let _ = state.try_add_pending_share(message.into_inner(), sender_id.clone());
+        let max_durable_faulty_size = state.validator_set().max_durable_faulty_size();
+        if state.get_total_received_shares_count() + state.get_total_pending_shares_count()
+            <= max_durable_faulty_size
+        {
+            return Ok(());
+        }
+        // compute signature, verifying the pending signature shares only if it is invalid
         let coin_name = CoinName::new(state.session_id(), &epoch)?;
-        let node_index: u64 = *state
-            .validator_set()
-            .as_indices()
-            .get(sender_id)
-            .unwrap()
-            .as_ref();
-        let public_key_share = state
-            .validator_key_shares()
-            .get_public_key_share(node_index);
         let coin_name_hash = threshold_crypto::hasher::hash(coin_name);
-        if !public_key_share.verify_with_hash(&signature_share, coin_name_hash) {
+        let pending_shares: Vec<(u64, Self::NodeId, SignatureShare)> = state
+            .take_pending_shares()
+            .into_iter()
+            .map(|(node_id, share)| {
+                let index: u64 = state.validator_set().index(&node_id).unwrap().into();
+                (index, node_id, share)
+            })
+            .collect();
+        let shares = state.get_received_shares().into_iter().chain(
+            pending_shares
+                .iter()
+                .map(|(index, _, share)| (*index, share)),
+        );
+        let (signature, invalid_indices) = match state
+            .validator_key_shares()
+            .public_key_shares()
+            .combine_signatures_robust(shares, coin_name_hash)
+        {
+            Ok((signature, invalid_indices)) => (Some(signature), invalid_indices),
+            Err(threshold_crypto::Error::NotEnoughValidShares { invalid_indices }) => {
+                (None, invalid_indices)
+            }
+            Err(cause) => return Err(cause.into()),
+        };
+        // shares left out of a valid signature are kept unverified, as they are no longer needed
+        for (index, node_id, share) in pending_shares {
+            if invalid_indices.contains(&index) {
+                state.push_fault_log(FaultLog {
+                    sender_id: node_id,
+                    message: BinaryAgreementMessage {
+                        epoch,
+                        content: BinaryAgreementMessageContent::Coin(CommonCoinMessage::from(
+                            share,
+                        )),
+                    },
+                    fault_type: FaultType::InvalidSignatureShare,
+                });
+            } else {
+                let _ = state.try_add_received_shares(share, node_id);
+            }
+        }
+        if let Some(signature) = signature {
+            state.set_coin_output(signature.parity());
+        }
+        Ok(())
+    }
+
+    fn handle_prop(
+        &self,
+        sender_id: &Self::NodeId,
+        epoch: Epoch,
+        message: PropMessage,
+        state: &mut BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>,
+    ) -> Result<()> {
+        let value = message.into_inner();
+        // a node proposes at most one value besides ⊥.
+        let other_value = value.map(|value| !value);
+        let fault_type = if other_value.is_some() && state.has_received_prop(other_value, sender_id)
+        {
+            Some(FaultType::ConflictingValue)
+        } else if !state.try_add_received_prop(value, sender_id.clone()) {
+            Some(FaultType::DuplicateProp)
+        } else {
+            None
+        };
+        if let Some(fault_type) = fault_type {
             state.push_fault_log(FaultLog {
                 sender_id: sender_id.clone(),
                 message: BinaryAgreementMessage {
                     epoch,
-                    content: BinaryAgreementMessageContent::Coin(CommonCoinMessage::from(
-                        signature_share,
-                    )),
+                    content: BinaryAgreementMessageContent::Prop(PropMessage::from(value)),
                 },
-                fault_type: FaultType::InvalidSignatureShare,
+                fault_type,
             });
             return Ok(());
         }
-        let _ = state.try_add_received_shares(signature_share, sender_id.clone());
-        if state.is_coin_decided() {
-            Ok(())
-        } else {
-            if state.get_total_received_shares_count()
-                > state.validator_set().max_durable_faulty_size()
-            {
-                // compute signature
-                let public_key_shares = state.validator_key_shares().public_key_shares();
-                let shares = state.get_received_shares();
-                let signature = public_key_shares.combine_signatures(shares.into_iter())?;
-                let master_public_key = state
-                    .validator_key_shares()
-                    .public_key_shares()
-                    .public_key();
-                if master_public_key.verify_with_hash(&signature, coin_name_hash) {
-                    state.set_coin_output(signature.parity());
-                } else {
-                    return Err(Error::InvalidCombinedSignature);
-                }
+        let count = state.get_received_prop_count(value);
+        let max_durable_faulty_size = state.validator_set().max_durable_faulty_size();
+
+        // upon receiving Prop(value) messages from f + 1 nodes, if neither Prop(value) nor Prop of
+        // the other value has been sent, multicast Prop(value). One of them comes from a correct
+        // node, which only proposes the majority value, so this never relays a second value.
+        if count > max_durable_faulty_size
+            && !(other_value.is_some() && state.has_sent_prop(other_value))
+            && state.try_add_sent_prop(value)
+        {
+            self.broadcast_prop_message(value, epoch, state.validators().clone())?;
+        }
+
+        // upon receiving Prop(value) messages from 2f + 1 nodes, update prop values.
+        if count > 2 * max_durable_faulty_size && state.try_update_prop_values(value) {
+            // multicast PropAux(value) only for the first prop value, so that every node reports
+            // one and the same value to all the others.
+            if !state.has_received_prop_aux(self.my_id()) {
+                self.broadcast_prop_aux_message(value, epoch, state.validators().clone())?;
+                let _ = state.try_add_received_prop_aux(value, self.my_id().clone());
             }
-            Ok(())
+        }
+        self.try_set_prop_aux_output(state)?;
+        Ok(())
+    }
+
+    fn handle_prop_aux(
+        &self,
+        sender_id: &Self::NodeId,
+        epoch: Epoch,
+        message: PropAuxMessage,
+        state: &mut BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>,
+    ) -> Result<()> {
+        let value = message.into_inner();
+        if !state.try_add_received_prop_aux(value, sender_id.clone()) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BinaryAgreementMessage {
+                    epoch,
+                    content: BinaryAgreementMessageContent::PropAux(PropAuxMessage::from(value)),
+                },
+                fault_type: FaultType::DuplicatePropAux,
+            });
+            return Ok(());
+        }
+        self.try_set_prop_aux_output(state)?;
+        Ok(())
+    }
+
+    fn try_set_prop_aux_output(
+        &self,
+        state: &mut BinaryAgreementState<Self::NodeId, Self::ValidatorIndex, Self::SessionId>,
+    ) -> Result<bool> {
+        if state.is_prop_aux_decided() {
+            return Ok(false);
+        }
+        if !state.is_aux_decided() {
+            // case if aux phase has not been completed.
+            return Ok(false);
+        }
+        let min_guarantee_size = state.validator_set().min_guarantee_size();
+        let (values, count) = state.get_accepted_prop_aux();
+        if count >= min_guarantee_size {
+            if values.contains(&Some(true)) && values.contains(&Some(false)) {
+                // unreachable with at most f faulty nodes, since correct nodes never propose
+                // different values.
+                return Err(Error::ConflictingPropValues {
+                    epoch: *state.epoch(),
+                });
+            }
+            state.set_prop_aux_output(values);
+            Ok(true)
+        } else {
+            Ok(false)
         }
     }
 
@@ -351,7 +685,7 @@ pub trait BinaryAgreement: fmt::Debug {
 
     fn broadcast_bval_message(
         &self,
-        value: bool,
+        message: BValMessage,
         epoch: Epoch,
         validators: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
     ) -> Result<()> {
@@ -361,7 +695,7 @@ pub trait BinaryAgreement: fmt::Debug {
                     node_id,
                     BinaryAgreementMessage {
                         epoch,
-                        content: BinaryAgreementMessageContent::BVal(BValMessage::from(value)),
+                        content: BinaryAgreementMessageContent::BVal(message.clone()),
                     },
                 );
             }
@@ -371,7 +705,7 @@ pub trait BinaryAgreement: fmt::Debug {
 
     fn broadcast_aux_message(
         &self,
-        value: bool,
+        message: AuxMessage,
         epoch: Epoch,
         validators: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
     ) -> Result<()> {
@@ -381,7 +715,7 @@ pub trait BinaryAgreement: fmt::Debug {
                     node_id,
                     BinaryAgreementMessage {
                         epoch,
-                        content: BinaryAgreementMessageContent::Aux(AuxMessage::from(value)),
+                        content: BinaryAgreementMessageContent::Aux(message.clone()),
                     },
                 );
             }
@@ -430,4 +764,46 @@ pub trait BinaryAgreement: fmt::Debug {
         }
         Ok(())
     }
+
+    fn broadcast_prop_message(
+        &self,
+        value: Option<bool>,
+        epoch: Epoch,
+        validators: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
+    ) -> Result<()> {
+        for (node_id, _index) in validators {
+            if node_id != *self.my_id() {
+                self.send_message(
+                    node_id,
+                    BinaryAgreementMessage {
+                        epoch,
+                        content: BinaryAgreementMessageContent::Prop(PropMessage::from(value)),
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn broadcast_prop_aux_message(
+        &self,
+        value: Option<bool>,
+        epoch: Epoch,
+        validators: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
+    ) -> Result<()> {
+        for (node_id, _index) in validators {
+            if node_id != *self.my_id() {
+                self.send_message(
+                    node_id,
+                    BinaryAgreementMessage {
+                        epoch,
+                        content: BinaryAgreementMessageContent::PropAux(PropAuxMessage::from(
+                            value,
+                        )),
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,24 +1,36 @@
+mod diagnostics;
 mod fault;
 mod round;
+pub use diagnostics::*;
 pub use fault::*;
 pub use round::*;
 
 use crate::{
     binary_values::{BinaryValueSet, BinaryValues},
     epoch::Epoch,
+    message::{AuxMessage, BValMessage},
     node::NodeId,
     session::SessionId,
     validator::{ValidatorIndex, ValidatorKeyShares, ValidatorSet},
+    validity::ExternalValidity,
+    variant::BinaryAgreementVariant,
 };
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use threshold_crypto::SignatureShare;
 
+/// Maximum number of fault logs kept per sender, all unknown senders counting as one. Further
+/// faults are only counted, so that a sender flooding e.g. messages of past epochs can't grow the
+/// state without bound.
+pub const MAX_FAULT_LOGS_PER_SENDER: usize = 16;
+
 pub struct BinaryAgreementState<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> {
     /// validators
     validator_set: ValidatorSet<NID, IDX>,
 
-    /// key share
-    validator_key_shares: ValidatorKeyShares,
+    /// key share, which the signature-free variant does without
+    validator_key_shares: Option<ValidatorKeyShares>,
+
+    variant: BinaryAgreementVariant,
 
     /// Session identifier, to prevent replaying messages in other instances.
     session_id: SID,
@@ -30,8 +42,17 @@ pub struct BinaryAgreementState<NID: NodeId, IDX: ValidatorIndex, SID: SessionId
 
     rounds: BTreeMap<Epoch, RoundState<NID>>,
 
+    /// External validity of `true`, if this is a validated agreement.
+    validity: Option<ExternalValidity>,
+
     fault_logs: Vec<FaultLog<NID>>,
 
+    /// Number of faults of each validator, including the ones whose logs were not kept.
+    fault_counts: BTreeMap<NID, usize>,
+
+    /// Number of faults of senders that are not validators.
+    unknown_sender_fault_count: usize,
+
     output: Option<bool>,
 }
 
@@ -43,12 +64,33 @@ impl<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> BinaryAgreementState<NID,
     ) -> Self {
         Self {
             validator_set,
-            validator_key_shares,
+            validator_key_shares: Some(validator_key_shares),
+            variant: BinaryAgreementVariant::ThresholdCoin,
+            session_id,
+            epoch: Epoch::default(),
+            estimated: None,
+            rounds: BTreeMap::from([(Epoch::default(), RoundState::new())]),
+            validity: None,
+            fault_logs: Vec::new(),
+            fault_counts: BTreeMap::new(),
+            unknown_sender_fault_count: 0,
+            output: None,
+        }
+    }
+
+    pub fn new_signature_free(validator_set: ValidatorSet<NID, IDX>, session_id: SID) -> Self {
+        Self {
+            validator_set,
+            validator_key_shares: None,
+            variant: BinaryAgreementVariant::SignatureFree,
             session_id,
             epoch: Epoch::default(),
             estimated: None,
             rounds: BTreeMap::from([(Epoch::default(), RoundState::new())]),
+            validity: None,
             fault_logs: Vec::new(),
+            fault_counts: BTreeMap::new(),
+            unknown_sender_fault_count: 0,
             output: None,
         }
     }
@@ -62,7 +104,20 @@ impl<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> BinaryAgreementState<NID,
     }
 
     pub fn validator_key_shares(&self) -> &ValidatorKeyShares {
-        &self.validator_key_shares
+        self.validator_key_shares
+            .as_ref()
+            .expect("threshold coin requires validator key shares...")
+    }
+
+    pub fn variant(&self) -> BinaryAgreementVariant {
+        self.variant
+    }
+
+    /// Switching to `ThresholdCoin` is ignored when there are no key shares to toss the coin with.
+    pub fn set_variant(&mut self, variant: BinaryAgreementVariant) {
+        if variant == BinaryAgreementVariant::SignatureFree || self.validator_key_shares.is_some() {
+            self.variant = variant;
+        }
     }
 
     pub fn session_id(&self) -> &SID {
@@ -76,9 +131,22 @@ impl<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> BinaryAgreementState<NID,
     pub fn increment_epoch(&mut self) {
         self.estimated = None;
         self.epoch.increment();
+        // messages of past epochs are rejected as faults, so their rounds are no longer needed.
+        self.rounds.clear();
         self.rounds.insert(self.epoch, RoundState::new());
     }
 
+    /// snapshot of the current round, to tell why it has not completed yet.
+    pub fn round_diagnostics(&self) -> RoundDiagnostics {
+        self.current_round()
+            .diagnostics(self.epoch, self.estimated, self.variant)
+    }
+
+    /// number of rounds kept in memory, which is always 1 since finished rounds are dropped.
+    pub fn round_count(&self) -> usize {
+        self.rounds.len()
+    }
+
     pub fn set_estimated(&mut self, value: bool) {
         self.estimated = Some(value);
     }
@@ -95,6 +163,14 @@ impl<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> BinaryAgreementState<NID,
             .expect("round state must be initialized...")
     }
 
+    pub fn received_messages(&self) -> usize {
+        self.current_round().received_messages()
+    }
+
+    pub fn count_received_message(&mut self) {
+        self.mut_current_round().count_received_message()
+    }
+
     pub fn try_add_sent_bval(&mut self, value: bool) -> bool {
         self.mut_current_round().try_add_sent_bval(value)
     }
@@ -121,6 +197,10 @@ impl<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> BinaryAgreementState<NID,
             .try_add_received_aux(value, sender_id)
     }
 
+    pub fn has_received_aux(&self, value: bool, sender_id: &NID) -> bool {
+        self.current_round().has_received_aux(value, sender_id)
+    }
+
     pub fn get_received_aux_count(&self, value: bool) -> usize {
         self.current_round().get_received_aux_count(value)
     }
@@ -179,6 +259,19 @@ impl<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> BinaryAgreementState<NID,
         self.current_round().get_total_received_shares_count()
     }
 
+    pub fn try_add_pending_share(&mut self, value: SignatureShare, sender_id: NID) -> bool {
+        self.mut_current_round()
+            .try_add_pending_share(value, sender_id)
+    }
+
+    pub fn get_total_pending_shares_count(&self) -> usize {
+        self.current_round().get_total_pending_shares_count()
+    }
+
+    pub fn take_pending_shares(&mut self) -> BTreeMap<NID, SignatureShare> {
+        self.mut_current_round().take_pending_shares()
+    }
+
     pub fn get_received_shares(&self) -> Vec<(u64, &SignatureShare)> {
         let received_shares = self.current_round().received_shares();
         received_shares
@@ -202,12 +295,130 @@ impl<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> BinaryAgreementState<NID,
         self.mut_current_round().set_coin_output(value)
     }
 
+    pub fn try_add_sent_prop(&mut self, value: Option<bool>) -> bool {
+        self.mut_current_round().try_add_sent_prop(value)
+    }
+
+    pub fn has_sent_prop(&self, value: Option<bool>) -> bool {
+        self.current_round().has_sent_prop(value)
+    }
+
+    pub fn has_received_prop(&self, value: Option<bool>, sender_id: &NID) -> bool {
+        self.current_round().has_received_prop(value, sender_id)
+    }
+
+    pub fn try_add_received_prop(&mut self, value: Option<bool>, sender_id: NID) -> bool {
+        self.mut_current_round()
+            .try_add_received_prop(value, sender_id)
+    }
+
+    pub fn get_received_prop_count(&self, value: Option<bool>) -> usize {
+        self.current_round().get_received_prop_count(value)
+    }
+
+    pub fn try_update_prop_values(&mut self, value: Option<bool>) -> bool {
+        self.mut_current_round().try_update_prop_values(value)
+    }
+
+    pub fn has_received_prop_aux(&self, sender_id: &NID) -> bool {
+        self.current_round().has_received_prop_aux(sender_id)
+    }
+
+    pub fn try_add_received_prop_aux(&mut self, value: Option<bool>, sender_id: NID) -> bool {
+        self.mut_current_round()
+            .try_add_received_prop_aux(value, sender_id)
+    }
+
+    pub fn get_accepted_prop_aux(&self) -> (BTreeSet<Option<bool>>, usize) {
+        self.current_round().get_accepted_prop_aux()
+    }
+
+    pub fn is_prop_aux_decided(&self) -> bool {
+        self.current_round().is_prop_aux_decided()
+    }
+
+    pub fn get_prop_aux_output(&self) -> Option<&BTreeSet<Option<bool>>> {
+        self.current_round().get_prop_aux_output()
+    }
+
+    pub fn set_prop_aux_output(&mut self, values: BTreeSet<Option<bool>>) {
+        self.mut_current_round().set_prop_aux_output(values)
+    }
+
+    pub fn set_external_validity(&mut self, validity: ExternalValidity) {
+        self.validity = Some(validity)
+    }
+
+    pub fn is_validated(&self) -> bool {
+        self.validity.is_some()
+    }
+
+    /// Returns a valid proof for `true` if we have seen one. If the agreement is validated and has
+    /// decided `true`, this is always `Some`.
+    pub fn get_validity_proof(&self) -> Option<&Vec<u8>> {
+        self.validity.as_ref().and_then(|validity| validity.proof())
+    }
+
+    /// Returns `true` if a message for `value` may be accepted with the given proof, and keeps the
+    /// proof if it is the first valid one. Only `true` needs a proof in a validated agreement.
+    pub fn accept_validity_proof(&mut self, value: bool, proof: Option<&Vec<u8>>) -> bool {
+        let validity = match self.validity.as_mut() {
+            Some(validity) if value => validity,
+            _ => return true,
+        };
+        match proof {
+            Some(proof) if validity.validate(proof) => {
+                let _ = validity.try_set_proof(proof.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn bval_message(&self, value: bool) -> BValMessage {
+        BValMessage::with_proof(value, self.proof_for(value))
+    }
+
+    pub fn aux_message(&self, value: bool) -> AuxMessage {
+        AuxMessage::with_proof(value, self.proof_for(value))
+    }
+
+    fn proof_for(&self, value: bool) -> Option<Vec<u8>> {
+        if value {
+            self.get_validity_proof().cloned()
+        } else {
+            None
+        }
+    }
+
     pub fn fault_logs(&self) -> &Vec<FaultLog<NID>> {
         &self.fault_logs
     }
 
+    /// Keeps the fault log unless `MAX_FAULT_LOGS_PER_SENDER` logs of its sender are already
+    /// kept, and counts the fault either way.
     pub fn push_fault_log(&mut self, fault_log: FaultLog<NID>) {
-        self.fault_logs.push(fault_log);
+        let count = if self.validator_set.contains(&fault_log.sender_id) {
+            self.fault_counts
+                .entry(fault_log.sender_id.clone())
+                .or_default()
+        } else {
+            &mut self.unknown_sender_fault_count
+        };
+        *count += 1;
+        if *count <= MAX_FAULT_LOGS_PER_SENDER {
+            self.fault_logs.push(fault_log);
+        }
+    }
+
+    /// Number of faults of the validator, including the ones whose logs were not kept.
+    pub fn fault_count(&self, sender_id: &NID) -> usize {
+        self.fault_counts.get(sender_id).copied().unwrap_or(0)
+    }
+
+    /// Number of faults of senders that are not validators.
+    pub fn unknown_sender_fault_count(&self) -> usize {
+        self.unknown_sender_fault_count
     }
 
     pub fn is_decided(&self) -> bool {
@@ -226,3 +437,83 @@ impl<NID: NodeId, IDX: ValidatorIndex, SID: SessionId> BinaryAgreementState<NID,
         (self.output, self.fault_logs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{BValMessage, BinaryAgreementMessage, BinaryAgreementMessageContent};
+    use core::fmt;
+
+    #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug, Hash)]
+    struct Index(u64);
+
+    impl From<Index> for u64 {
+        fn from(value: Index) -> Self {
+            value.0
+        }
+    }
+
+    impl AsRef<u64> for Index {
+        fn as_ref(&self) -> &u64 {
+            &self.0
+        }
+    }
+
+    impl fmt::Display for Index {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    #[test]
+    fn test_memory_is_bounded_over_many_epochs() {
+        let validator_indices = (1u16..=4).map(|id| (id, Index(id as u64 - 1))).collect();
+        let validator_set = ValidatorSet::new(validator_indices).unwrap();
+        let mut state = BinaryAgreementState::new_signature_free(validator_set, "test");
+        let stale_message = BinaryAgreementMessage {
+            epoch: Epoch::default(),
+            content: BinaryAgreementMessageContent::BVal(BValMessage::with_proof(true, None)),
+        };
+        let epochs = 1000;
+        for _ in 0..epochs {
+            for sender_id in 1..=4 {
+                let _ = state.try_add_received_bval(true, sender_id);
+                let _ = state.try_add_received_aux(true, sender_id);
+            }
+            state.increment_epoch();
+            // every validator and an unknown sender keep sending messages of the first epoch.
+            for sender_id in 1..=5 {
+                state.push_fault_log(FaultLog {
+                    sender_id,
+                    message: stale_message.clone(),
+                    fault_type: FaultType::EpochMismatched {
+                        current_epoch: *state.epoch(),
+                        incoming_epoch: Epoch::default(),
+                    },
+                });
+            }
+            assert_eq!(1, state.round_count());
+            assert!(state.fault_logs().len() <= 5 * MAX_FAULT_LOGS_PER_SENDER);
+        }
+        assert_eq!(5 * MAX_FAULT_LOGS_PER_SENDER, state.fault_logs().len());
+        for sender_id in 1..=4 {
+            assert_eq!(epochs, state.fault_count(&sender_id));
+        }
+        assert_eq!(epochs, state.unknown_sender_fault_count());
+    }
+
+    #[test]
+    fn test_finished_rounds_are_pruned() {
+        let validator_indices = (1u16..=4).map(|id| (id, Index(id as u64 - 1))).collect();
+        let validator_set = ValidatorSet::new(validator_indices).unwrap();
+        let mut state = BinaryAgreementState::new_signature_free(validator_set, "test");
+        for _ in 0..100 {
+            let _ = state.try_add_received_bval(true, 1);
+            let _ = state.try_add_received_aux(true, 1);
+            state.increment_epoch();
+            assert_eq!(1, state.round_count());
+            assert_eq!(0, state.get_received_bval_count(true));
+        }
+        assert_eq!(100, state.epoch().to_u64());
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{binary_values::BinaryValues, epoch::Epoch};
+use std::collections::BTreeMap;
+
+/// The phase a round is waiting in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundPhase {
+    /// waiting for `2 * f + 1` BVals of the same value
+    BinValues,
+    /// waiting for `N - f` Aux messages whose values are in bin_values
+    AuxQuorum,
+    /// waiting for `N - f` Conf messages whose values are in bin_values
+    ConfQuorum,
+    /// waiting for `f + 1` valid coin signature shares
+    Coin,
+    /// waiting for `N - f` PropAux messages whose values have been delivered
+    PropAuxQuorum,
+    /// every phase has completed
+    Completed,
+}
+
+/// Snapshot of the message counts of a round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundDiagnostics {
+    pub epoch: Epoch,
+    pub phase: RoundPhase,
+    pub estimated: Option<bool>,
+    pub received_messages: usize,
+    pub received_bval: BTreeMap<bool, usize>,
+    pub bin_values: Option<BinaryValues>,
+    pub received_aux: BTreeMap<bool, usize>,
+    pub aux_output: Option<BinaryValues>,
+    pub received_conf: BTreeMap<BinaryValues, usize>,
+    pub conf_output: Option<BinaryValues>,
+    pub received_coin_shares: usize,
+    pub coin_output: Option<bool>,
+    pub received_prop: BTreeMap<Option<bool>, usize>,
+    pub received_prop_aux: BTreeMap<Option<bool>, usize>,
+}
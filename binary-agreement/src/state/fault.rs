@@ -10,7 +10,15 @@ pub enum FaultType {
     DuplicateBVal,
     DuplicateAux,
     DuplicateConf,
+    DuplicateProp,
+    DuplicatePropAux,
+    /// The sender has already sent a Prop or, in the signature-free variant, an Aux for the other value.
+    ConflictingValue,
+    /// The message belongs to a phase the running variant does not have.
+    UnexpectedMessage,
     InvalidSignatureShare,
+    MissingValidityProof,
+    InvalidValidityProof,
 }
 
 #[derive(Debug, Clone)]
@@ -1,11 +1,16 @@
+use super::{RoundDiagnostics, RoundPhase};
 use crate::{
     binary_values::{BinaryValueMultimap, BinaryValueSet, BinaryValues, BinaryValuesMultimap},
+    epoch::Epoch,
     node::NodeId,
+    variant::BinaryAgreementVariant,
 };
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use threshold_crypto::SignatureShare;
 
 pub struct RoundState<NID: NodeId> {
+    /// number of messages received in this round
+    received_messages: usize,
     /// BVal message that we have already sent
     sent_bval: BinaryValueSet,
     /// received BVal messages
@@ -22,13 +27,27 @@ pub struct RoundState<NID: NodeId> {
     conf_output: BinaryValueSet,
     /// All received threshold signature shares.
     received_shares: BTreeMap<NID, SignatureShare>,
+    /// Received threshold signature shares not verified yet, checked as a batch once enough of
+    /// them have been received to toss the coin.
+    pending_shares: BTreeMap<NID, SignatureShare>,
     /// output of common coin
     coin_output: Option<bool>,
+    /// Prop messages that we have already sent
+    sent_prop: BTreeSet<Option<bool>>,
+    /// received Prop messages
+    received_prop: BTreeMap<Option<bool>, BTreeSet<NID>>,
+    /// The set of Prop values for which `2 * f + 1 Prop`s have been received.
+    prop_values: BTreeSet<Option<bool>>,
+    /// received PropAux messages, at most one per sender
+    received_prop_aux: BTreeMap<NID, Option<bool>>,
+    /// output of PropAux phase
+    prop_aux_output: Option<BTreeSet<Option<bool>>>,
 }
 
 impl<NID: NodeId> RoundState<NID> {
     pub fn new() -> Self {
         Self {
+            received_messages: 0,
             sent_bval: BinaryValueSet::default(),
             received_bval: BinaryValueMultimap::default(),
             bin_values: BinaryValueSet::default(),
@@ -37,10 +56,24 @@ impl<NID: NodeId> RoundState<NID> {
             received_conf: BinaryValuesMultimap::default(),
             conf_output: BinaryValueSet::default(),
             received_shares: BTreeMap::new(),
+            pending_shares: BTreeMap::new(),
             coin_output: None,
+            sent_prop: BTreeSet::new(),
+            received_prop: BTreeMap::new(),
+            prop_values: BTreeSet::new(),
+            received_prop_aux: BTreeMap::new(),
+            prop_aux_output: None,
         }
     }
 
+    pub fn received_messages(&self) -> usize {
+        self.received_messages
+    }
+
+    pub fn count_received_message(&mut self) {
+        self.received_messages += 1
+    }
+
     pub fn try_add_sent_bval(&mut self, value: bool) -> bool {
         self.sent_bval.insert(value)
     }
@@ -65,6 +98,10 @@ impl<NID: NodeId> RoundState<NID> {
         self.received_aux[value].insert(sender_id)
     }
 
+    pub fn has_received_aux(&self, value: bool, sender_id: &NID) -> bool {
+        self.received_aux[value].contains(sender_id)
+    }
+
     pub fn get_received_aux_count(&self, value: bool) -> usize {
         self.received_aux[value].len()
     }
@@ -138,6 +175,25 @@ impl<NID: NodeId> RoundState<NID> {
         &self.received_shares
     }
 
+    /// Returns false if a share of the sender has already been received.
+    pub fn try_add_pending_share(&mut self, value: SignatureShare, sender_id: NID) -> bool {
+        if self.received_shares.contains_key(&sender_id)
+            || self.pending_shares.contains_key(&sender_id)
+        {
+            return false;
+        }
+        self.pending_shares.insert(sender_id, value);
+        true
+    }
+
+    pub fn get_total_pending_shares_count(&self) -> usize {
+        self.pending_shares.len()
+    }
+
+    pub fn take_pending_shares(&mut self) -> BTreeMap<NID, SignatureShare> {
+        std::mem::take(&mut self.pending_shares)
+    }
+
     pub fn is_coin_decided(&self) -> bool {
         self.coin_output.is_some()
     }
@@ -149,4 +205,142 @@ impl<NID: NodeId> RoundState<NID> {
     pub fn set_coin_output(&mut self, value: bool) {
         self.coin_output = Some(value)
     }
+
+    pub fn try_add_sent_prop(&mut self, value: Option<bool>) -> bool {
+        self.sent_prop.insert(value)
+    }
+
+    pub fn has_sent_prop(&self, value: Option<bool>) -> bool {
+        self.sent_prop.contains(&value)
+    }
+
+    pub fn has_received_prop(&self, value: Option<bool>, sender_id: &NID) -> bool {
+        matches!(self.received_prop.get(&value), Some(senders) if senders.contains(sender_id))
+    }
+
+    pub fn try_add_received_prop(&mut self, value: Option<bool>, sender_id: NID) -> bool {
+        self.received_prop
+            .entry(value)
+            .or_default()
+            .insert(sender_id)
+    }
+
+    pub fn get_received_prop_count(&self, value: Option<bool>) -> usize {
+        self.received_prop
+            .get(&value)
+            .map_or(0, |senders| senders.len())
+    }
+
+    pub fn prop_values(&self) -> &BTreeSet<Option<bool>> {
+        &self.prop_values
+    }
+
+    pub fn try_update_prop_values(&mut self, value: Option<bool>) -> bool {
+        self.prop_values.insert(value)
+    }
+
+    pub fn has_received_prop_aux(&self, sender_id: &NID) -> bool {
+        self.received_prop_aux.contains_key(sender_id)
+    }
+
+    pub fn try_add_received_prop_aux(&mut self, value: Option<bool>, sender_id: NID) -> bool {
+        if self.has_received_prop_aux(&sender_id) {
+            return false;
+        }
+        self.received_prop_aux.insert(sender_id, value);
+        true
+    }
+
+    /// PropAux values whose Prop has been delivered, with the number of distinct senders.
+    pub fn get_accepted_prop_aux(&self) -> (BTreeSet<Option<bool>>, usize) {
+        let mut values = BTreeSet::new();
+        let mut count = 0;
+        for value in self.received_prop_aux.values() {
+            if self.prop_values.contains(value) {
+                values.insert(*value);
+                count += 1;
+            }
+        }
+        (values, count)
+    }
+
+    pub fn is_prop_aux_decided(&self) -> bool {
+        self.prop_aux_output.is_some()
+    }
+
+    pub fn get_prop_aux_output(&self) -> Option<&BTreeSet<Option<bool>>> {
+        self.prop_aux_output.as_ref()
+    }
+
+    pub fn set_prop_aux_output(&mut self, values: BTreeSet<Option<bool>>) {
+        self.prop_aux_output = Some(values)
+    }
+
+    pub fn phase(&self, variant: BinaryAgreementVariant) -> RoundPhase {
+        if self.bin_values.is_not_set() {
+            return RoundPhase::BinValues;
+        }
+        if !self.is_aux_decided() {
+            return RoundPhase::AuxQuorum;
+        }
+        match variant {
+            BinaryAgreementVariant::ThresholdCoin => {
+                if !self.is_conf_decided() {
+                    RoundPhase::ConfQuorum
+                } else if !self.is_coin_decided() {
+                    RoundPhase::Coin
+                } else {
+                    RoundPhase::Completed
+                }
+            }
+            BinaryAgreementVariant::SignatureFree => {
+                if !self.is_prop_aux_decided() {
+                    RoundPhase::PropAuxQuorum
+                } else {
+                    RoundPhase::Completed
+                }
+            }
+        }
+    }
+
+    pub fn diagnostics(
+        &self,
+        epoch: Epoch,
+        estimated: Option<bool>,
+        variant: BinaryAgreementVariant,
+    ) -> RoundDiagnostics {
+        let mut received_prop_aux = BTreeMap::new();
+        for value in self.received_prop_aux.values() {
+            *received_prop_aux.entry(*value).or_insert(0) += 1;
+        }
+        RoundDiagnostics {
+            epoch,
+            phase: self.phase(variant),
+            estimated,
+            received_messages: self.received_messages,
+            received_bval: BTreeMap::from([
+                (false, self.received_bval[false].len()),
+                (true, self.received_bval[true].len()),
+            ]),
+            bin_values: self.bin_values.get(),
+            received_aux: BTreeMap::from([
+                (false, self.received_aux[false].len()),
+                (true, self.received_aux[true].len()),
+            ]),
+            aux_output: self.aux_output.get(),
+            received_conf: [BinaryValues::False, BinaryValues::True, BinaryValues::Both]
+                .into_iter()
+                .map(|values| (values, self.received_conf[values].len()))
+                .collect(),
+            conf_output: self.conf_output.get(),
+            received_coin_shares: self.received_shares.len(),
+            coin_output: self.coin_output,
+            received_prop: self
+                .received_prop
+                .iter()
+                .map(|(value, senders)| (*value, senders.len()))
+                .collect(),
+            received_prop_aux,
+        }
+    }
 }
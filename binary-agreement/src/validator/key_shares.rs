@@ -23,6 +23,13 @@ impl ValidatorKeyShares {
         &self.public_key_shares
     }
 
+    /// Returns whether the secret key share is the one of the validator with the given index,
+    /// to be checked when loading key shares, before taking part in any consensus.
+    pub fn verify(&self, index: u64) -> bool {
+        self.public_key_shares
+            .verify_secret_key_share(index, &self.secret_key_share)
+    }
+
     pub fn get_public_key_share(&self, index: u64) -> PublicKeyShare {
         self.public_key_shares.public_key_share(index)
     }
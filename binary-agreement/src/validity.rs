@@ -0,0 +1,53 @@
+use core::fmt;
+
+type Predicate = dyn Fn(&[u8]) -> bool + Send;
+
+/// External validity predicate of a validated binary agreement.
+///
+/// `BVal(true)` and `Aux(true)` messages must carry a proof accepted by the predicate, so deciding
+/// `true` guarantees that at least one valid proof exists.
+pub struct ExternalValidity {
+    predicate: Box<Predicate>,
+    /// The first valid proof we have seen.
+    proof: Option<Vec<u8>>,
+}
+
+impl ExternalValidity {
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&[u8]) -> bool + Send + 'static,
+    {
+        Self {
+            predicate: Box::new(predicate),
+            proof: None,
+        }
+    }
+
+    /// Returns `true` if the proof satisfies the application predicate.
+    pub fn validate(&self, proof: &[u8]) -> bool {
+        (self.predicate)(proof)
+    }
+
+    pub fn proof(&self) -> Option<&Vec<u8>> {
+        self.proof.as_ref()
+    }
+
+    /// Keeps the proof if we don't have one yet. The proof must have been validated beforehand.
+    pub fn try_set_proof(&mut self, proof: Vec<u8>) -> bool {
+        if self.proof.is_some() {
+            return false;
+        }
+        self.proof = Some(proof);
+        true
+    }
+}
+
+impl fmt::Debug for ExternalValidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ExternalValidity {{ has_proof: {} }}",
+            self.proof.is_some()
+        )
+    }
+}
@@ -0,0 +1,9 @@
+/// Which binary agreement protocol an instance runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryAgreementVariant {
+    /// BVal → Aux → Conf → Coin, with a common coin from threshold signatures.
+    ThresholdCoin,
+    /// BVal → Aux → Prop → PropAux, with a local coin. It needs no key shares,
+    /// at the cost of an expected number of rounds exponential in the number of validators.
+    SignatureFree,
+}
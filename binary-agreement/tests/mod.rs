@@ -1,14 +1,17 @@
 use binary_agreement::epoch::Epoch;
-use binary_agreement::message::{BinaryAgreementMessage, BinaryAgreementMessageContent};
+use binary_agreement::message::{
+    AuxMessage, BValMessage, BinaryAgreementMessage, BinaryAgreementMessageContent, PropAuxMessage,
+    PropMessage,
+};
 use binary_agreement::node::NodeMessage;
 use binary_agreement::validator::{ValidatorKeyShares, ValidatorSet};
-use binary_agreement::BinaryAgreement;
+use binary_agreement::{BinaryAgreement, BinaryAgreementState, Error, FaultType, RoundPhase};
 use logger::prelude::*;
 use rand::thread_rng;
 use std::collections::{BTreeMap, VecDeque};
 use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 use std::{fmt, thread};
-use threshold_crypto::{SecretKeyShare, SecretKeyShares};
+use threshold_crypto::{PublicKeyShares, SecretKeyShare, SecretKeyShares};
 
 type NodeId = u16;
 
@@ -53,6 +56,8 @@ struct TestNode {
     message_receiver: Receiver<NodeMessage<NodeId>>,
     message_router: BTreeMap<NodeId, SyncSender<NodeMessage<NodeId>>>,
     message_queue: BTreeMap<Epoch, VecDeque<NodeMessage<NodeId>>>,
+    max_rounds: Option<u64>,
+    max_round_messages: Option<usize>,
 }
 
 impl fmt::Debug for TestNode {
@@ -111,6 +116,8 @@ impl BinaryAgreement for TestNode {
             BinaryAgreementMessageContent::Aux(_) => "AUX",
             BinaryAgreementMessageContent::Conf(_) => "CONF",
             BinaryAgreementMessageContent::Coin(_) => "COIN",
+            BinaryAgreementMessageContent::Prop(_) => "PROP",
+            BinaryAgreementMessageContent::PropAux(_) => "PROP_AUX",
         };
         debug!(
             "[send message]{} -> {}: {:?} {}",
@@ -128,6 +135,14 @@ impl BinaryAgreement for TestNode {
     fn on_next_epoch(&mut self, epoch: &Epoch) {
         debug!("[begin epoch]{}: {:?}", self.id, epoch);
     }
+
+    fn max_rounds(&self) -> Option<u64> {
+        self.max_rounds
+    }
+
+    fn max_round_messages(&self) -> Option<usize> {
+        self.max_round_messages
+    }
 }
 
 fn gen_random_secret_key_shares(threshold: usize) -> SecretKeyShares {
@@ -135,18 +150,18 @@ fn gen_random_secret_key_shares(threshold: usize) -> SecretKeyShares {
     SecretKeyShares::random(threshold, &mut rnd)
 }
 
-#[test]
-fn test_simple_procedure() {
-    // init logger
-    let mut builder = logger::default::DefaultLoggerBuilder::new();
-    builder.is_async(true);
-    builder.level(logger::Level::Debug);
-    let _logger = builder.build();
+type Network = (
+    BTreeMap<NodeId, TestNode>,
+    ValidatorSet<NodeId, Index>,
+    BTreeMap<NodeId, SecretKeyShare>,
+    PublicKeyShares,
+);
 
+fn init_network(size: NodeId) -> Network {
     let channel_size = 10000;
     let mut message_receivers: BTreeMap<NodeId, Receiver<NodeMessage<NodeId>>> = BTreeMap::new();
     let mut message_router: BTreeMap<NodeId, SyncSender<NodeMessage<NodeId>>> = BTreeMap::new();
-    for id in 1..=4 {
+    for id in 1..=size {
         let (sender, receiver) = sync_channel(channel_size);
         message_receivers.insert(id, receiver);
         message_router.insert(id, sender);
@@ -161,11 +176,12 @@ fn test_simple_procedure() {
                 message_receiver,
                 message_router: message_router.clone(),
                 message_queue: BTreeMap::new(),
+                max_rounds: None,
+                max_round_messages: None,
             },
         );
     }
 
-    let session_id: SessionId = 1;
     let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
     let validator_set = ValidatorSet::new(validator_indices).unwrap();
     let secret_key_shares = gen_random_secret_key_shares(validator_set.max_durable_faulty_size());
@@ -178,6 +194,24 @@ fn test_simple_procedure() {
             (node_id.clone(), secret_key_share)
         })
         .collect();
+    (
+        nodes,
+        validator_set,
+        secret_key_share_map,
+        public_key_shares,
+    )
+}
+
+#[test]
+fn test_simple_procedure() {
+    // init logger
+    let mut builder = logger::default::DefaultLoggerBuilder::new();
+    builder.is_async(true);
+    builder.level(logger::Level::Debug);
+    let _logger = builder.build();
+
+    let session_id: SessionId = 1;
+    let (nodes, validator_set, secret_key_share_map, public_key_shares) = init_network(4);
     let mut handles = BTreeMap::new();
     for (id, mut node) in nodes {
         let validator_set = validator_set.clone();
@@ -205,3 +239,273 @@ fn test_simple_procedure() {
         }
     }
 }
+
+#[test]
+fn test_validated_procedure() {
+    let session_id: SessionId = 2;
+    let valid_proof = b"valid proof".to_vec();
+    let (nodes, validator_set, secret_key_share_map, public_key_shares) = init_network(4);
+    let mut handles = BTreeMap::new();
+    for (id, mut node) in nodes {
+        let validator_set = validator_set.clone();
+        let secret_key_share_map = secret_key_share_map.clone();
+        let public_key_shares = public_key_shares.clone();
+        let valid_proof = valid_proof.clone();
+        let handle = thread::spawn(move || {
+            // the last node doesn't know any valid proof.
+            let (input, proof) = if id <= 3 {
+                (true, Some(valid_proof.clone()))
+            } else {
+                (false, None)
+            };
+            let secret_key_share = secret_key_share_map.get(&id).unwrap().clone();
+            let validator_key_shares = ValidatorKeyShares::new(secret_key_share, public_key_shares);
+            node.propose_validated(
+                input,
+                proof,
+                move |proof| proof == valid_proof.as_slice(),
+                validator_set,
+                validator_key_shares,
+                session_id,
+            )
+        });
+        handles.insert(id, handle);
+    }
+    for (id, handle) in handles {
+        let result = handle.join().unwrap();
+        match result {
+            Ok(state) => {
+                assert!(state.is_decided());
+                let output = state.get_output().unwrap();
+                assert!(output);
+                // deciding true means that every node has got a valid proof.
+                assert_eq!(Some(&valid_proof), state.get_validity_proof());
+            }
+            Err(err) => {
+                panic!("id: {:?}, err: {:?}", id, err);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_validated_procedure_requires_proof() {
+    let session_id: SessionId = 3;
+    let (mut nodes, validator_set, secret_key_share_map, public_key_shares) = init_network(4);
+    let node = nodes.get_mut(&1).unwrap();
+    let secret_key_share = secret_key_share_map.get(&1).unwrap().clone();
+    let validator_key_shares = ValidatorKeyShares::new(secret_key_share, public_key_shares);
+    let result = node.propose_validated(
+        true,
+        Some(b"invalid proof".to_vec()),
+        |proof| proof == b"valid proof",
+        validator_set.clone(),
+        validator_key_shares.clone(),
+        session_id,
+    );
+    assert!(matches!(result, Err(Error::InvalidValidityProof)));
+    let result = node.propose_validated(
+        true,
+        None,
+        |proof| proof == b"valid proof",
+        validator_set,
+        validator_key_shares,
+        session_id,
+    );
+    assert!(matches!(result, Err(Error::MissingValidityProof)));
+}
+
+#[test]
+fn test_signature_free_procedure() {
+    let session_id: SessionId = 4;
+    let (nodes, validator_set, _secret_key_share_map, _public_key_shares) = init_network(4);
+    let mut handles = BTreeMap::new();
+    for (id, mut node) in nodes {
+        let validator_set = validator_set.clone();
+        let handle = thread::spawn(move || {
+            let input = id <= 3;
+            let result = node.propose_signature_free(input, validator_set, session_id);
+            // keep the node alive, since the others may still send messages to it.
+            (result, node)
+        });
+        handles.insert(id, handle);
+    }
+    let results: BTreeMap<NodeId, _> = handles
+        .into_iter()
+        .map(|(id, handle)| (id, handle.join().unwrap()))
+        .collect();
+    for (id, (result, _node)) in results {
+        match result {
+            Ok(state) => {
+                assert!(state.is_decided());
+                let output = state.get_output().unwrap();
+                assert!(output);
+                assert!(state.fault_logs().is_empty());
+            }
+            Err(err) => {
+                panic!("id: {:?}, err: {:?}", id, err);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_round_limit_exceeded() {
+    let session_id: SessionId = 5;
+    let (mut nodes, validator_set, _secret_key_share_map, _public_key_shares) = init_network(4);
+    let node = nodes.get_mut(&1).unwrap();
+    node.max_rounds = Some(1);
+    let mut state = BinaryAgreementState::new_signature_free(validator_set, session_id);
+    let _ = state.try_add_received_bval(true, 1);
+    let _ = state.try_add_received_bval(true, 2);
+    let _ = state.try_add_received_bval(false, 3);
+    match node.start_next_epoch(true, &mut state) {
+        Err(Error::RoundLimitExceeded { limit, diagnostics }) => {
+            assert_eq!(1, limit);
+            assert_eq!(Epoch::from(0), diagnostics.epoch);
+            assert_eq!(RoundPhase::BinValues, diagnostics.phase);
+            assert_eq!(Some(&2), diagnostics.received_bval.get(&true));
+            assert_eq!(Some(&1), diagnostics.received_bval.get(&false));
+            assert_eq!(None, diagnostics.bin_values);
+        }
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+    // the limit has not been reached yet with two rounds.
+    node.max_rounds = Some(2);
+    assert!(node.start_next_epoch(true, &mut state).is_ok());
+    assert_eq!(Epoch::from(1), *state.epoch());
+}
+
+#[test]
+fn test_round_message_limit_exceeded() {
+    let session_id: SessionId = 8;
+    let epoch = Epoch::from(0);
+    let (mut nodes, validator_set, secret_key_share_map, public_key_shares) = init_network(4);
+    let mut node = nodes.remove(&1).unwrap();
+    node.max_round_messages = Some(4);
+    // the round never gets enough Aux messages to start the Conf phase.
+    let mut messages = vec![];
+    for sender_id in 2..=4 {
+        messages.push((sender_id, BinaryAgreementMessageContent::BVal(true.into())));
+    }
+    messages.push((2, BinaryAgreementMessageContent::Aux(true.into())));
+    messages.push((2, BinaryAgreementMessageContent::BVal(true.into())));
+    let router = node.message_router.get(&1).unwrap().clone();
+    for (sender_id, content) in messages {
+        router
+            .send(NodeMessage::BinaryAgreementMessage {
+                sender_id,
+                message: BinaryAgreementMessage { epoch, content },
+            })
+            .unwrap();
+    }
+    let secret_key_share = secret_key_share_map.get(&1).unwrap().clone();
+    let validator_key_shares = ValidatorKeyShares::new(secret_key_share, public_key_shares);
+    match node.propose(true, validator_set, validator_key_shares, session_id) {
+        Err(Error::RoundMessageLimitExceeded { limit, diagnostics }) => {
+            assert_eq!(4, limit);
+            assert_eq!(epoch, diagnostics.epoch);
+            assert_eq!(RoundPhase::AuxQuorum, diagnostics.phase);
+            assert_eq!(4, diagnostics.received_messages);
+            assert_eq!(Some(&2), diagnostics.received_aux.get(&true));
+        }
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_signature_free_conflicting_props() {
+    let session_id: SessionId = 6;
+    let epoch = Epoch::from(0);
+    let (mut nodes, validator_set, _secret_key_share_map, _public_key_shares) = init_network(4);
+    let node = nodes.remove(&1).unwrap();
+    let mut state = BinaryAgreementState::new_signature_free(validator_set, session_id);
+    for sender_id in 2..=4 {
+        node.handle_bval(&sender_id, epoch, BValMessage::from(true), &mut state)
+            .unwrap();
+    }
+    for sender_id in 2..=3 {
+        node.handle_aux(&sender_id, epoch, AuxMessage::from(true), &mut state)
+            .unwrap();
+    }
+    // the faulty node votes for both values.
+    node.handle_aux(&4, epoch, AuxMessage::from(true), &mut state)
+        .unwrap();
+    node.handle_aux(&4, epoch, AuxMessage::from(false), &mut state)
+        .unwrap();
+    // Prop(false) reaches f + 1 after Prop(true) has been sent, and the faulty node proposes both.
+    node.handle_prop(&2, epoch, PropMessage::from(Some(true)), &mut state)
+        .unwrap();
+    node.handle_prop(&3, epoch, PropMessage::from(Some(false)), &mut state)
+        .unwrap();
+    node.handle_prop(&4, epoch, PropMessage::from(Some(false)), &mut state)
+        .unwrap();
+    node.handle_prop(&4, epoch, PropMessage::from(Some(true)), &mut state)
+        .unwrap();
+
+    let sent_props: Vec<Option<bool>> = nodes[&2]
+        .message_receiver
+        .try_iter()
+        .filter_map(|message| match message {
+            NodeMessage::BinaryAgreementMessage {
+                message:
+                    BinaryAgreementMessage {
+                        content: BinaryAgreementMessageContent::Prop(message),
+                        ..
+                    },
+                ..
+            } => Some(message.into_inner()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(vec![Some(true)], sent_props);
+    let conflicting_senders: Vec<NodeId> = state
+        .fault_logs()
+        .iter()
+        .filter(|fault_log| matches!(fault_log.fault_type, FaultType::ConflictingValue))
+        .map(|fault_log| fault_log.sender_id)
+        .collect();
+    assert_eq!(vec![4, 4], conflicting_senders);
+}
+
+#[test]
+fn test_signature_free_conflicting_prop_aux_output() {
+    let session_id: SessionId = 7;
+    let epoch = Epoch::from(0);
+    let (mut nodes, validator_set, _secret_key_share_map, _public_key_shares) = init_network(6);
+    let node = nodes.remove(&1).unwrap();
+    let mut state = BinaryAgreementState::new_signature_free(validator_set, session_id);
+    for sender_id in 2..=6 {
+        node.handle_bval(&sender_id, epoch, BValMessage::from(true), &mut state)
+            .unwrap();
+    }
+    for sender_id in 2..=5 {
+        node.handle_aux(&sender_id, epoch, AuxMessage::from(true), &mut state)
+            .unwrap();
+    }
+    // more than f nodes lie, so that both values get 2f + 1 Props.
+    for (sender_id, value) in [(2, true), (3, true), (4, false), (5, false), (6, false)] {
+        node.handle_prop(
+            &sender_id,
+            epoch,
+            PropMessage::from(Some(value)),
+            &mut state,
+        )
+        .unwrap();
+    }
+    for (sender_id, value) in [(2, true), (4, false), (5, false)] {
+        node.handle_prop_aux(
+            &sender_id,
+            epoch,
+            PropAuxMessage::from(Some(value)),
+            &mut state,
+        )
+        .unwrap();
+    }
+    let result = node.handle_prop_aux(&6, epoch, PropAuxMessage::from(Some(false)), &mut state);
+    assert!(matches!(
+        result,
+        Err(Error::ConflictingPropValues { epoch: conflicting_epoch }) if conflicting_epoch == epoch
+    ));
+    assert!(!state.is_prop_aux_decided());
+}
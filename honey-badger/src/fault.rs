@@ -1,10 +1,12 @@
-use crate::{message::DecryptionShareMessage, node::NodeId};
+use crate::{message::DecryptionShareMessage, node::NodeId, session::SessionId};
+use reliable_broadcast::merkle::{MerkleHasher, Sha3Hasher};
 
 #[derive(Debug, Clone)]
-pub enum FaultLog<ID: NodeId> {
-    ReliableBroadcast(reliable_broadcast::FaultLog<ID>),
+pub enum FaultLog<ID: NodeId, SID: SessionId, H: MerkleHasher = Sha3Hasher> {
+    ReliableBroadcast(reliable_broadcast::FaultLog<ID, SID, H>),
     BinaryAgreement(binary_agreement::FaultLog<ID>),
     DecryptionShare(DecryptionShareFaultLog<ID>),
+    Contribution(ContributionFaultLog<ID>),
 }
 
 #[derive(Debug, Clone)]
@@ -19,3 +21,20 @@ pub struct DecryptionShareFaultLog<ID: NodeId> {
     pub message: DecryptionShareMessage<ID>,
     pub fault_type: DecryptionShareFaultType,
 }
+
+#[derive(Debug, Clone)]
+pub enum ContributionFaultType {
+    /// The contribution isn't labeled with its session, epoch and proposer.
+    UnexpectedLabel,
+    /// The ciphertext doesn't verify, so no decryption share is computed for it.
+    InvalidCiphertext,
+    /// The payload can't be decrypted with the key the decryption shares yield.
+    InvalidPayload,
+}
+
+/// A fault of a proposer, whose contribution is delivered by the common subset but ignored.
+#[derive(Debug, Clone)]
+pub struct ContributionFaultLog<ID: NodeId> {
+    pub proposer_id: ID,
+    pub fault_type: ContributionFaultType,
+}
@@ -7,6 +7,7 @@ mod fault;
 mod message;
 mod node;
 mod procedure;
+mod session;
 mod transaction;
 mod validator;
 
@@ -15,5 +16,6 @@ pub use fault::*;
 pub use message::*;
 pub use node::*;
 pub use procedure::*;
+pub use session::*;
 pub use transaction::*;
 pub use validator::*;
@@ -1,27 +1,86 @@
 use crate::{
-    BatchTransactions, DecryptionShareFaultLog, DecryptionShareFaultType, DecryptionShareMessage,
-    Epoch, Error, FaultLog, NodeId, NodeMessage, Result, Transaction, ValidatorIndex,
-    VerifiedTransactions,
+    BatchTransactions, ContributionFaultLog, ContributionFaultType, DecryptionShareFaultLog,
+    DecryptionShareFaultType, DecryptionShareMessage, Epoch, Error, FaultLog, NodeId, NodeMessage,
+    Result, SessionId, Transaction, ValidatorIndex, VerifiedTransactions,
 };
 use asynchronous_common_subset::AsynchronousCommonSubset;
 use core::fmt;
 use rand::Rng;
+use reliable_broadcast::merkle::{MerkleHasher, Sha3Hasher};
 use std::collections::BTreeMap;
-use threshold_crypto::{Ciphertext, DecryptionShare, PublicKeyShares, SecretKeyShare};
+use threshold_crypto::{DecryptionShare, HybridCiphertext, PublicKeyShares, SecretKeyShare};
 
-pub struct HoneyBadgerOutput<ID: NodeId, TX: Transaction> {
+pub struct HoneyBadgerOutput<
+    ID: NodeId,
+    SID: SessionId,
+    TX: Transaction,
+    H: MerkleHasher = Sha3Hasher,
+> {
     pub verified_transactions: VerifiedTransactions<TX>,
-    pub fault_logs: Vec<FaultLog<ID>>,
+    pub fault_logs: Vec<FaultLog<ID, SID, H>>,
 }
 
+type HoneyBadgerResult<HB> = Result<
+    HoneyBadgerOutput<
+        <HB as HoneyBadger>::NodeId,
+        <HB as HoneyBadger>::SessionId,
+        <HB as HoneyBadger>::Transaction,
+        <HB as HoneyBadger>::Hasher,
+    >,
+>;
+
+/// Binds a contribution to the session and epoch it is proposed in and to its proposer, so that it
+/// can't be replayed as another contribution.
+fn contribution_label<ACS: AsynchronousCommonSubset>(
+    acs: &ACS,
+    epoch: &Epoch,
+    proposer_id: &ACS::NodeId,
+    validator_indices: &BTreeMap<ACS::NodeId, ACS::ValidatorIndex>,
+) -> Vec<u8> {
+    let session_id = acs.get_reliable_broadcast_session_id(proposer_id);
+    let proposer_index: u64 = validator_indices[proposer_id].into();
+    encode_contribution_label(&session_id, epoch, proposer_index)
+}
+
+fn encode_contribution_label<SID: SessionId>(
+    session_id: &SID,
+    epoch: &Epoch,
+    proposer_index: u64,
+) -> Vec<u8> {
+    bincode::serialize(&(session_id.to_string(), epoch.value(), proposer_index))
+        .expect("strings and integers are always serializable")
+}
+
+/// Computes the decryption share of a contribution, if it is labeled as expected and valid.
+fn decrypt_contribution_share(
+    ciphertext: &HybridCiphertext,
+    label: &[u8],
+    secret_key_share: &SecretKeyShare,
+) -> core::result::Result<DecryptionShare, ContributionFaultType> {
+    let key_ciphertext = ciphertext.as_key_ciphertext();
+    if key_ciphertext.as_label() != label {
+        // e.g. a contribution replayed from another epoch or proposer
+        return Err(ContributionFaultType::UnexpectedLabel);
+    }
+    secret_key_share
+        .decrypt_share(key_ciphertext)
+        .ok_or(ContributionFaultType::InvalidCiphertext)
+}
+
+type PendingDecryptionShareMessages<ID> = BTreeMap<ID, BTreeMap<ID, DecryptionShareMessage<ID>>>;
+
 pub trait HoneyBadger: fmt::Debug {
     type NodeId: NodeId + 'static;
     type ValidatorIndex: ValidatorIndex + 'static;
+    type SessionId: SessionId + 'static;
+    type Hasher: MerkleHasher + 'static;
     type Transaction: Transaction;
     type BatchTransactions: BatchTransactions<Transaction = Self::Transaction>;
     type AsynchronousCommonSubset: AsynchronousCommonSubset<
         NodeId = Self::NodeId,
         ValidatorIndex = Self::ValidatorIndex,
+        SessionId = Self::SessionId,
+        Hasher = Self::Hasher,
     >;
     type Rng: Rng;
 
@@ -47,19 +106,20 @@ pub trait HoneyBadger: fmt::Debug {
         validator_indices: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
         secret_key_share: SecretKeyShare,
         public_key_shares: PublicKeyShares,
-    ) -> Result<HoneyBadgerOutput<Self::NodeId, Self::Transaction>> {
-        let mut fault_logs: Vec<FaultLog<Self::NodeId>> = Vec::new();
+    ) -> HoneyBadgerResult<Self> {
+        let mut fault_logs: Vec<FaultLog<Self::NodeId, Self::SessionId, Self::Hasher>> = Vec::new();
 
         let contribution_bytes = transactions
             .serialize()
             .map_err(|_| Error::BatchTransactionsSerializationError)?;
+        let mut acs = self.create_asynchronous_common_subset_instance(epoch);
+        let label = contribution_label(&acs, epoch, self.my_id(), &validator_indices);
         let rng = self.rng();
         let ciphertext = public_key_shares
             .public_key()
-            .encrypt_with_rng(rng, contribution_bytes);
+            .encrypt_hybrid_with_label_and_rng(rng, contribution_bytes, label);
         let encrypted_contribution_bytes = bincode::serialize(&ciphertext)
             .map_err(|err| Error::EncryptedBatchTransactionsSerializationError { cause: *err })?;
-        let mut acs = self.create_asynchronous_common_subset_instance(epoch);
         let acs_result = acs.propose(
             encrypted_contribution_bytes,
             validator_indices.clone(),
@@ -73,7 +133,11 @@ pub trait HoneyBadger: fmt::Debug {
             BTreeMap<Self::NodeId, DecryptionShare>,
         > = BTreeMap::new();
         // { proposer_id: ciphertext }
-        let mut received_proposed_ciphertext: BTreeMap<Self::NodeId, Ciphertext> = BTreeMap::new();
+        let mut received_proposed_ciphertext: BTreeMap<Self::NodeId, HybridCiphertext> =
+            BTreeMap::new();
+        // { proposer_id: { sender_id: message } }, verified as a batch once they may suffice
+        let mut pending_decryption_share_messages: PendingDecryptionShareMessages<Self::NodeId> =
+            BTreeMap::new();
 
         // for each acs output, broadcast its decryption share
         for (proposer_id, rbc_out) in acs_result.as_reliable_broadcast_outputs() {
@@ -82,11 +146,22 @@ pub trait HoneyBadger: fmt::Debug {
                 continue;
             }
             let rbc_output_bytes = rbc_out.as_ref().unwrap(); // contribution encrypted with public key share by proposer
-            let proposed_ciphertext: Ciphertext =
-                bincode::deserialize(&rbc_output_bytes).map_err(|err| {
-                    Error::EncryptedBatchTransactionsSerializationError { cause: *err }
+            let proposed_ciphertext: HybridCiphertext = bincode::deserialize(rbc_output_bytes)
+                .map_err(|err| Error::EncryptedBatchTransactionsSerializationError {
+                    cause: *err,
                 })?;
-            let decryption_share = secret_key_share.decrypt_share_force(&proposed_ciphertext);
+            let label = contribution_label(&acs, epoch, proposer_id, &validator_indices);
+            let decryption_share =
+                match decrypt_contribution_share(&proposed_ciphertext, &label, &secret_key_share) {
+                    Ok(decryption_share) => decryption_share,
+                    Err(fault_type) => {
+                        fault_logs.push(FaultLog::Contribution(ContributionFaultLog {
+                            proposer_id: proposer_id.clone(),
+                            fault_type,
+                        }));
+                        continue;
+                    }
+                };
             received_proposed_ciphertext.insert(proposer_id.clone(), proposed_ciphertext);
             // broadcast decryption share
             for (node_id, _index) in &validator_indices {
@@ -145,38 +220,56 @@ pub trait HoneyBadger: fmt::Debug {
                         }));
                         continue;
                     }
-                    let proposed_ciphertext = received_proposed_ciphertext
-                        .get(&message.proposer_id)
-                        .unwrap();
-                    let sender_index = validator_indices.get(&sender_id).unwrap().clone();
-                    let public_key_share =
-                        public_key_shares.public_key_share(*(sender_index.as_ref()));
-                    if !public_key_share
-                        .verify_decryption_share(&message.decryption_share, proposed_ciphertext)
+                    let proposer_id = message.proposer_id.clone();
+                    let verified_count = received_decryption_shares[&proposer_id].len();
+                    if verified_count > max_durable_faulty_size
+                        || received_decryption_shares[&proposer_id].contains_key(&sender_id)
                     {
-                        fault_logs.push(FaultLog::DecryptionShare(DecryptionShareFaultLog {
-                            sender_id,
-                            message,
-                            fault_type: DecryptionShareFaultType::InvalidDecryptionShare,
-                        }));
+                        // enough decryption shares have already been verified for the proposer
                         continue;
                     }
+                    let pending_messages = pending_decryption_share_messages
+                        .entry(proposer_id.clone())
+                        .or_default();
+                    pending_messages.entry(sender_id).or_insert(message);
+                    if verified_count + pending_messages.len() <= max_durable_faulty_size {
+                        continue;
+                    }
+                    // verify the pending decryption shares of the proposer at once
+                    let pending_messages = pending_decryption_share_messages
+                        .remove(&proposer_id)
+                        .unwrap();
+                    let index_of = |node_id: &Self::NodeId| -> u64 {
+                        (*validator_indices.get(node_id).unwrap()).into()
+                    };
+                    let invalid_indices = public_key_shares.verify_decryption_shares(
+                        pending_messages.iter().map(|(sender_id, message)| {
+                            (index_of(sender_id), &message.decryption_share)
+                        }),
+                        received_proposed_ciphertext[&proposer_id].as_key_ciphertext(),
+                    );
+                    let decryption_shares =
+                        received_decryption_shares.get_mut(&proposer_id).unwrap();
+                    for (sender_id, message) in pending_messages {
+                        if invalid_indices.contains(&index_of(&sender_id)) {
+                            fault_logs.push(FaultLog::DecryptionShare(DecryptionShareFaultLog {
+                                sender_id,
+                                message,
+                                fault_type: DecryptionShareFaultType::InvalidDecryptionShare,
+                            }));
+                        } else {
+                            decryption_shares.insert(sender_id, message.decryption_share);
+                        }
+                    }
+                    // check received enough decryption shares for each proposed ciphertext by acs
                     if received_decryption_shares
-                        .get_mut(&message.proposer_id)
-                        .unwrap()
-                        .insert(sender_id, message.decryption_share)
+                        .iter()
+                        .find(|(_proposer_id, decryption_shares)| {
+                            decryption_shares.len() <= max_durable_faulty_size
+                        })
                         .is_none()
                     {
-                        // if newly received, check received enough decryption shares for each proposed ciphertext by acs
-                        if received_decryption_shares
-                            .iter()
-                            .find(|(_proposer_id, decryption_shares)| {
-                                decryption_shares.len() <= max_durable_faulty_size
-                            })
-                            .is_none()
-                        {
-                            break;
-                        }
+                        break;
                     }
                 }
             }
@@ -191,10 +284,18 @@ pub trait HoneyBadger: fmt::Debug {
                 let index: u64 = validator_index.into();
                 (index, decryption_share)
             });
-            let plain_text = public_key_shares
-                .decrypt(shares, &ciphertext)
-                .map_err(|cause| Error::DecryptDecryptionSharesError { cause })?;
-            computed_plan_text_contributions.insert(proposer_id, plain_text);
+            match public_key_shares.decrypt_hybrid(shares, &ciphertext) {
+                Ok(plain_text) => {
+                    computed_plan_text_contributions.insert(proposer_id, plain_text);
+                }
+                Err(_) => {
+                    // the payload doesn't authenticate under the decrypted key
+                    fault_logs.push(FaultLog::Contribution(ContributionFaultLog {
+                        proposer_id,
+                        fault_type: ContributionFaultType::InvalidPayload,
+                    }));
+                }
+            }
         }
 
         // combine & sort transactions outputted by the process above -> block!
@@ -212,3 +313,38 @@ pub trait HoneyBadger: fmt::Debug {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+    use threshold_crypto::SecretKeyShares;
+
+    #[test]
+    fn test_mislabeled_contribution_is_rejected() {
+        let mut rng = thread_rng();
+        let sk_shares = SecretKeyShares::random(1, &mut rng);
+        let pk_shares = sk_shares.public_keys();
+        let sk_share = sk_shares.secret_key_share(0u64);
+        let epoch = Epoch::from(5u64);
+        let label = encode_contribution_label(&"session", &epoch, 1);
+        let ciphertext = pk_shares.public_key().encrypt_hybrid_with_label_and_rng(
+            &mut rng,
+            b"contribution",
+            label.clone(),
+        );
+        assert!(decrypt_contribution_share(&ciphertext, &label, &sk_share).is_ok());
+
+        // replayed in another session or epoch, or as another proposer's contribution
+        for other_label in [
+            encode_contribution_label(&"other session", &epoch, 1),
+            encode_contribution_label(&"session", &Epoch::from(9u64), 1),
+            encode_contribution_label(&"session", &epoch, 2),
+        ] {
+            assert!(matches!(
+                decrypt_contribution_share(&ciphertext, &other_label, &sk_share),
+                Err(ContributionFaultType::UnexpectedLabel)
+            ));
+        }
+    }
+}
@@ -0,0 +1 @@
+pub use asynchronous_common_subset::session::SessionId;
@@ -0,0 +1,35 @@
+use thiserror::Error as ThisError;
+
+#[derive(ThisError, Debug)]
+pub enum Error {
+    #[error("ReliableBroadcastError: {cause}")]
+    ReliableBroadcastError { cause: reliable_broadcast::Error },
+    #[error("BinaryAgreementError: {cause}")]
+    BinaryAgreementError { cause: binary_agreement::Error },
+    #[error("ThresholdCryptoError: {cause}")]
+    ThresholdCryptoError { cause: threshold_crypto::Error },
+    #[error("Input value does not satisfy the external validity predicate")]
+    InvalidInput,
+    #[error("Reliable broadcast instances stopped before N - f values were delivered")]
+    InsufficientDeliveries,
+    #[error("The procedure was terminated before a value was agreed")]
+    Terminated,
+}
+
+impl From<reliable_broadcast::Error> for Error {
+    fn from(cause: reliable_broadcast::Error) -> Self {
+        Self::ReliableBroadcastError { cause }
+    }
+}
+
+impl From<binary_agreement::Error> for Error {
+    fn from(cause: binary_agreement::Error) -> Self {
+        Self::BinaryAgreementError { cause }
+    }
+}
+
+impl From<threshold_crypto::Error> for Error {
+    fn from(cause: threshold_crypto::Error) -> Self {
+        Self::ThresholdCryptoError { cause }
+    }
+}
@@ -0,0 +1,16 @@
+mod error;
+pub use error::Error;
+pub type Result<T> = core::result::Result<T, Error>;
+
+pub mod message;
+pub mod node;
+pub mod session;
+pub mod validator;
+
+mod permutation;
+
+mod state;
+pub use state::*;
+
+mod procedure;
+pub use procedure::*;
@@ -0,0 +1,23 @@
+use threshold_crypto::SignatureShare;
+
+/// Signature share of the common coin that seeds the order of the binary agreement instances.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermutationCoinMessage(SignatureShare);
+
+impl PermutationCoinMessage {
+    pub fn into_inner(self) -> SignatureShare {
+        self.0
+    }
+}
+
+impl AsRef<SignatureShare> for PermutationCoinMessage {
+    fn as_ref(&self) -> &SignatureShare {
+        &self.0
+    }
+}
+
+impl From<SignatureShare> for PermutationCoinMessage {
+    fn from(value: SignatureShare) -> Self {
+        Self(value)
+    }
+}
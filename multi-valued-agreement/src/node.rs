@@ -0,0 +1,4 @@
+mod id;
+mod message;
+pub use id::NodeId;
+pub use message::NodeMessage;
@@ -0,0 +1,11 @@
+use crate::message::PermutationCoinMessage;
+use crate::node::NodeId;
+
+#[derive(Clone, PartialEq)]
+pub enum NodeMessage<ID: NodeId> {
+    PermutationCoinMessage {
+        sender_id: ID,
+        message: Box<PermutationCoinMessage>,
+    },
+    Terminate,
+}
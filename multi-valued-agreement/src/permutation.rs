@@ -0,0 +1,17 @@
+use group::Curve;
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaChaRng;
+use threshold_crypto::Signature;
+use tiny_keccak::{Hasher, Sha3};
+
+/// Shuffles `ids` with a generator seeded by the combined coin signature, so that every honest
+/// node derives the same order while no node can predict it before the coin is revealed.
+pub(crate) fn permute<ID>(signature: &Signature, mut ids: Vec<ID>) -> Vec<ID> {
+    let mut seed = [0u8; 32];
+    let mut hasher = Sha3::v256();
+    hasher.update(signature.as_ref().to_affine().to_compressed().as_ref());
+    hasher.finalize(&mut seed);
+    let mut rng = ChaChaRng::from_seed(seed);
+    ids.shuffle(&mut rng);
+    ids
+}
@@ -0,0 +1,361 @@
+use crate::{
+    message::PermutationCoinMessage,
+    node::{NodeId, NodeMessage},
+    permutation,
+    session::SessionId,
+    validator::ValidatorIndex,
+    Error, FaultLog, FaultType, MultiValuedAgreementState, PermutationCoinFaultLog,
+    ReliableBroadcastFaultLog, Result,
+};
+use binary_agreement::{coin_name::CoinName, epoch::Epoch, BinaryAgreement};
+use core::fmt;
+use reliable_broadcast::{merkle::MerkleHasher, ReliableBroadcast, ReliableBroadcastState};
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use std::thread;
+use threshold_crypto::{PublicKeyShares, SecretKeyShare, Signature, SignatureShare};
+
+type ReliableBroadcastOutput<ID, IDX, SID, H> = (
+    ID,
+    reliable_broadcast::Result<ReliableBroadcastState<ID, IDX, SID, H>>,
+);
+
+pub trait MultiValuedAgreement: fmt::Debug {
+    type NodeId: NodeId + 'static;
+    type ValidatorIndex: ValidatorIndex + 'static;
+    type SessionId: SessionId + 'static;
+    type Hasher: MerkleHasher;
+    type ReliableBroadcast: ReliableBroadcast<
+            NodeId = Self::NodeId,
+            ValidatorIndex = Self::ValidatorIndex,
+            SessionId = Self::SessionId,
+            Hasher = Self::Hasher,
+        > + Send
+        + 'static;
+    type BinaryAgreement: BinaryAgreement<
+        NodeId = Self::NodeId,
+        ValidatorIndex = Self::ValidatorIndex,
+        SessionId = Self::SessionId,
+    >;
+
+    fn my_id(&self) -> &Self::NodeId;
+
+    fn create_reliable_broadcast_instance(
+        &mut self,
+        target_id: &Self::NodeId,
+    ) -> Self::ReliableBroadcast;
+
+    fn terminate_reliable_broadcast(&self, target_id: &Self::NodeId);
+
+    fn get_reliable_broadcast_session_id(&self, target_id: &Self::NodeId) -> Self::SessionId;
+
+    /// Creates the binary agreement instance deciding on the value of `target_id` in the given
+    /// round. Instances of different rounds must not receive each other's messages.
+    fn create_binary_agreement_instance(
+        &mut self,
+        target_id: &Self::NodeId,
+        round: usize,
+    ) -> Self::BinaryAgreement;
+
+    /// Session id of the binary agreement instance deciding on the value of `target_id` in the
+    /// given round. It must differ for every pair of target and round.
+    fn get_binary_agreement_session_id(
+        &self,
+        target_id: &Self::NodeId,
+        round: usize,
+    ) -> Self::SessionId;
+
+    /// Session id of the permutation coin. It must differ from every binary agreement session id,
+    /// otherwise the permutation coin would coincide with the first coin of that instance.
+    fn get_permutation_session_id(&self) -> Self::SessionId;
+
+    fn next_message(&self) -> NodeMessage<Self::NodeId>;
+
+    fn send_message(&self, target_id: Self::NodeId, message: PermutationCoinMessage);
+
+    fn handle_terminate_message(&self) {
+        println!("{self:?} has just detected terminate message.");
+    }
+
+    /// External validity predicate on proposed values. Values for which this returns false are
+    /// treated as undelivered, so they never receive input 1 and can never be the output.
+    fn validate_value(&self, _value: &[u8]) -> bool {
+        true
+    }
+
+    /// Let {RBCi}N refer to N instances of the reliable broadcast protocol, where Pi is the sender of RBCi.
+    /// * upon receiving input vi, input vi to RBCi
+    /// * wait until valid values have been delivered from at least N − f instances of RBC.
+    /// * toss the permutation coin and derive a permutation π of [1..N] from it.
+    /// * for j = π(1), π(2), ..., run BAj with input 1 if vj has been delivered from RBCj, otherwise input 0.
+    /// * once BAj decides 1, wait for the output vj of RBCj and output vj.
+    /// * if every BAj decides 0, run them all again in a further round, with the inputs updated
+    ///   from the values delivered since. Values of honest proposers are eventually delivered to
+    ///   every honest node, so some round decides 1.
+    ///
+    /// Returns `Error::Terminated` if the procedure is terminated before a value is agreed.
+    fn propose(
+        &mut self,
+        input: Vec<u8>,
+        validator_indices: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
+        secret_key_share: SecretKeyShare,
+        public_key_shares: PublicKeyShares,
+    ) -> Result<MultiValuedAgreementState<Self::NodeId, Self::SessionId, Self::Hasher>> {
+        if !self.validate_value(&input) {
+            return Err(Error::InvalidInput);
+        }
+        // initialize state
+        let mut state = MultiValuedAgreementState::new();
+
+        let rb_validator_set =
+            reliable_broadcast::validator::ValidatorSet::new(validator_indices.clone())?;
+        let ba_validator_set =
+            binary_agreement::validator::ValidatorSet::new(validator_indices.clone())?;
+
+        // create N instances of RBC and start their procedure
+        let (rb_output_sender, rb_output_receiver) = mpsc::channel();
+        let mut rb_threads = BTreeMap::new();
+        for node_id in validator_indices.keys() {
+            let rb_instance = self.create_reliable_broadcast_instance(node_id);
+            let rb_session_id = self.get_reliable_broadcast_session_id(node_id);
+            let validator_set = rb_validator_set.clone();
+            let rb_output_sender = rb_output_sender.clone();
+            let node_id_for_rb_thread = node_id.clone();
+            let input = if node_id == self.my_id() {
+                Some(input.clone())
+            } else {
+                None
+            };
+            let rb_thread = thread::spawn(move || {
+                let rbc_out = match input {
+                    Some(input) => rb_instance.propose(input, validator_set, rb_session_id),
+                    None => rb_instance.execute(None, validator_set, rb_session_id),
+                };
+                rb_output_sender
+                    .send((node_id_for_rb_thread, rbc_out))
+                    .expect("could not send reliable broadcast output message...");
+            });
+            rb_threads.insert(node_id.clone(), rb_thread);
+        }
+        drop(rb_output_sender);
+
+        // wait for N - f valid values to be delivered
+        while state.count_reliable_broadcast_delivered() < ba_validator_set.min_guarantee_size() {
+            let rbc_out = rb_output_receiver
+                .recv()
+                .map_err(|_| Error::InsufficientDeliveries)?;
+            self.handle_reliable_broadcast_output(rbc_out, &mut state);
+        }
+
+        if let Some(signature) = self.toss_permutation_coin(
+            &secret_key_share,
+            &public_key_shares,
+            &ba_validator_set,
+            &mut state,
+        )? {
+            let ids = validator_indices.keys().cloned().collect();
+            state.set_permutation(permutation::permute(&signature, ids));
+        }
+
+        // run BA instances one by one in the order of the permutation, round after round
+        let validator_key_shares = binary_agreement::validator::ValidatorKeyShares::new(
+            secret_key_share,
+            public_key_shares,
+        );
+        let permutation = state.as_permutation().clone();
+        let mut round = 0;
+        'rounds: while !permutation.is_empty() {
+            for target_id in &permutation {
+                while let Ok(rbc_out) = rb_output_receiver.try_recv() {
+                    self.handle_reliable_broadcast_output(rbc_out, &mut state);
+                }
+                let ba_input = state.get_reliable_broadcast_output(target_id).is_some();
+                let mut ba_instance = self.create_binary_agreement_instance(target_id, round);
+                let session_id = self.get_binary_agreement_session_id(target_id, round);
+                let ba_out = ba_instance.propose(
+                    ba_input,
+                    ba_validator_set.clone(),
+                    validator_key_shares.clone(),
+                    session_id,
+                )?;
+                state.set_binary_agreement_state(target_id.clone(), ba_out);
+                match state.get_binary_agreement_output(target_id) {
+                    Some(true) => {
+                        // some honest node has delivered the value, so RBCj eventually delivers
+                        // it to us as well
+                        while !state.is_reliable_broadcast_completed(target_id) {
+                            let rbc_out = rb_output_receiver
+                                .recv()
+                                .map_err(|_| Error::InsufficientDeliveries)?;
+                            self.handle_reliable_broadcast_output(rbc_out, &mut state);
+                        }
+                        if let Some(value) = state.get_reliable_broadcast_output(target_id) {
+                            let value = value.clone();
+                            state.set_output(target_id.clone(), value);
+                        }
+                        break 'rounds;
+                    }
+                    Some(false) => {}
+                    // the binary agreement has been terminated
+                    None => break 'rounds,
+                }
+            }
+            round += 1;
+            state.set_round(round);
+        }
+
+        // terminate unfinished RB process
+        for node_id in validator_indices.keys() {
+            if !state.is_reliable_broadcast_completed(node_id) {
+                self.terminate_reliable_broadcast(node_id);
+            }
+        }
+        for (_node_id, rb_thread) in rb_threads {
+            rb_thread.join().unwrap();
+        }
+        while let Ok(rbc_out) = rb_output_receiver.try_recv() {
+            self.handle_reliable_broadcast_output(rbc_out, &mut state);
+        }
+        if !state.is_decided() {
+            return Err(Error::Terminated);
+        }
+        Ok(state)
+    }
+
+    fn handle_reliable_broadcast_output(
+        &self,
+        rbc_out: ReliableBroadcastOutput<
+            Self::NodeId,
+            Self::ValidatorIndex,
+            Self::SessionId,
+            Self::Hasher,
+        >,
+        state: &mut MultiValuedAgreementState<Self::NodeId, Self::SessionId, Self::Hasher>,
+    ) {
+        let (node_id, rbc_out) = rbc_out;
+        let output = match rbc_out {
+            Ok(rbc_out) => {
+                let (output, fault_logs) = rbc_out.into_output_and_logs();
+                state.set_reliable_broadcast_fault_logs(node_id.clone(), fault_logs);
+                output.filter(|value| self.validate_value(value))
+            }
+            Err(cause) => {
+                state.push_fault_log(FaultLog::ReliableBroadcast(ReliableBroadcastFaultLog {
+                    proposer_id: node_id.clone(),
+                    cause: cause.to_string(),
+                }));
+                None
+            }
+        };
+        state.set_reliable_broadcast_output(node_id, output);
+    }
+
+    /// Returns the combined coin signature, or `None` if the procedure was terminated before
+    /// enough signature shares had been received.
+    fn toss_permutation_coin(
+        &self,
+        secret_key_share: &SecretKeyShare,
+        public_key_shares: &PublicKeyShares,
+        validator_set: &binary_agreement::validator::ValidatorSet<
+            Self::NodeId,
+            Self::ValidatorIndex,
+        >,
+        state: &mut MultiValuedAgreementState<Self::NodeId, Self::SessionId, Self::Hasher>,
+    ) -> Result<Option<Signature>> {
+        let coin_name = CoinName::new(&self.get_permutation_session_id(), &Epoch::from(0))?;
+        let coin_name_hash = threshold_crypto::hasher::hash(&coin_name);
+        let signature_share = secret_key_share.sign(&coin_name);
+        self.broadcast_permutation_coin_message(
+            signature_share.clone(),
+            validator_set.as_indices(),
+        );
+        let _ = state.try_add_received_coin_share(signature_share, self.my_id().clone());
+        let max_durable_faulty_size = validator_set.max_durable_faulty_size();
+        // signature shares not verified yet, checked as a batch once they may suffice
+        let mut pending_messages: BTreeMap<Self::NodeId, PermutationCoinMessage> = BTreeMap::new();
+        loop {
+            if state.get_total_received_coin_shares_count() + pending_messages.len()
+                > max_durable_faulty_size
+            {
+                // combine the shares, verifying the pending ones only if the signature is invalid
+                let pending_messages = std::mem::take(&mut pending_messages);
+                let index_of = |node_id: &Self::NodeId| -> u64 {
+                    validator_set.index(node_id).unwrap().into()
+                };
+                let shares = state
+                    .get_received_coin_shares(validator_set)
+                    .into_iter()
+                    .chain(
+                        pending_messages
+                            .iter()
+                            .map(|(sender_id, message)| (index_of(sender_id), message.as_ref())),
+                    );
+                let (signature, invalid_indices) =
+                    match public_key_shares.combine_signatures_robust(shares, coin_name_hash) {
+                        Ok((signature, invalid_indices)) => (Some(signature), invalid_indices),
+                        Err(threshold_crypto::Error::NotEnoughValidShares { invalid_indices }) => {
+                            (None, invalid_indices)
+                        }
+                        Err(cause) => return Err(cause.into()),
+                    };
+                for (sender_id, message) in pending_messages {
+                    if invalid_indices.contains(&index_of(&sender_id)) {
+                        state.push_fault_log(FaultLog::PermutationCoin(PermutationCoinFaultLog {
+                            sender_id,
+                            message: Box::new(message),
+                            fault_type: FaultType::InvalidSignatureShare,
+                        }));
+                    } else {
+                        let _ = state.try_add_received_coin_share(message.into_inner(), sender_id);
+                    }
+                }
+                if signature.is_some() {
+                    return Ok(signature);
+                }
+                continue;
+            }
+            match self.next_message() {
+                NodeMessage::PermutationCoinMessage { sender_id, message } => {
+                    if !validator_set.contains(&sender_id) {
+                        state.push_fault_log(FaultLog::PermutationCoin(PermutationCoinFaultLog {
+                            sender_id,
+                            message,
+                            fault_type: FaultType::UnknownSender,
+                        }));
+                        continue;
+                    }
+                    if state.has_received_coin_share(&sender_id)
+                        || pending_messages.contains_key(&sender_id)
+                    {
+                        state.push_fault_log(FaultLog::PermutationCoin(PermutationCoinFaultLog {
+                            sender_id,
+                            message,
+                            fault_type: FaultType::DuplicateCoinShare,
+                        }));
+                        continue;
+                    }
+                    pending_messages.insert(sender_id, *message);
+                }
+                NodeMessage::Terminate => {
+                    self.handle_terminate_message();
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    fn broadcast_permutation_coin_message(
+        &self,
+        signature_share: SignatureShare,
+        validators: &BTreeMap<Self::NodeId, Self::ValidatorIndex>,
+    ) {
+        for node_id in validators.keys() {
+            if node_id != self.my_id() {
+                self.send_message(
+                    node_id.clone(),
+                    PermutationCoinMessage::from(signature_share.clone()),
+                );
+            }
+        }
+    }
+}
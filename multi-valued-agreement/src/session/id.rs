@@ -0,0 +1,4 @@
+use core::fmt;
+
+pub trait SessionId: Clone + Eq + fmt::Display + fmt::Debug + Send + Sync {}
+impl<ID> SessionId for ID where ID: Clone + Eq + fmt::Display + fmt::Debug + Send + Sync {}
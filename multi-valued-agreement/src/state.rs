@@ -0,0 +1,191 @@
+mod fault;
+pub use fault::*;
+
+use crate::{node::NodeId, session::SessionId};
+use binary_agreement::BinaryAgreementState;
+use reliable_broadcast::merkle::{MerkleHasher, Sha3Hasher};
+use std::collections::{btree_map::Entry, BTreeMap};
+use threshold_crypto::SignatureShare;
+
+#[derive(Debug, Clone)]
+pub struct MultiValuedAgreementState<ID: NodeId, SID: SessionId, H: MerkleHasher = Sha3Hasher> {
+    reliable_broadcast_outputs: BTreeMap<ID, Option<Vec<u8>>>,
+    reliable_broadcast_fault_logs: BTreeMap<ID, Vec<reliable_broadcast::FaultLog<ID, SID, H>>>,
+    received_coin_shares: BTreeMap<ID, SignatureShare>,
+    permutation: Vec<ID>,
+    round: usize,
+    binary_agreement_outputs: BTreeMap<ID, Option<bool>>,
+    binary_agreement_fault_logs: BTreeMap<ID, Vec<binary_agreement::FaultLog<ID>>>,
+    output: Option<(ID, Vec<u8>)>,
+    fault_logs: Vec<FaultLog<ID>>,
+}
+
+impl<ID: NodeId, SID: SessionId, H: MerkleHasher> MultiValuedAgreementState<ID, SID, H> {
+    pub(crate) fn new() -> Self {
+        Self {
+            reliable_broadcast_outputs: BTreeMap::default(),
+            reliable_broadcast_fault_logs: BTreeMap::default(),
+            received_coin_shares: BTreeMap::default(),
+            permutation: Vec::default(),
+            round: 0,
+            binary_agreement_outputs: BTreeMap::default(),
+            binary_agreement_fault_logs: BTreeMap::default(),
+            output: None,
+            fault_logs: Vec::default(),
+        }
+    }
+
+    pub fn is_decided(&self) -> bool {
+        self.output.is_some()
+    }
+
+    /// The agreed value, paired with the id of the node that proposed it.
+    pub fn as_output(&self) -> Option<&(ID, Vec<u8>)> {
+        self.output.as_ref()
+    }
+
+    pub fn into_output(self) -> Option<Vec<u8>> {
+        self.output.map(|(_, value)| value)
+    }
+
+    pub(crate) fn set_output(&mut self, node_id: ID, value: Vec<u8>) {
+        self.output = Some((node_id, value));
+    }
+
+    pub fn as_reliable_broadcast_outputs(&self) -> &BTreeMap<ID, Option<Vec<u8>>> {
+        &self.reliable_broadcast_outputs
+    }
+
+    pub(crate) fn is_reliable_broadcast_completed(&self, node_id: &ID) -> bool {
+        self.reliable_broadcast_outputs.contains_key(node_id)
+    }
+
+    pub(crate) fn get_reliable_broadcast_output(&self, node_id: &ID) -> Option<&Vec<u8>> {
+        self.reliable_broadcast_outputs
+            .get(node_id)
+            .and_then(|output| output.as_ref())
+    }
+
+    pub(crate) fn count_reliable_broadcast_delivered(&self) -> usize {
+        self.reliable_broadcast_outputs
+            .values()
+            .filter(|output| output.is_some())
+            .count()
+    }
+
+    pub(crate) fn set_reliable_broadcast_output(&mut self, node_id: ID, output: Option<Vec<u8>>) {
+        self.reliable_broadcast_outputs.insert(node_id, output);
+    }
+
+    pub(crate) fn set_reliable_broadcast_fault_logs(
+        &mut self,
+        node_id: ID,
+        fault_logs: Vec<reliable_broadcast::FaultLog<ID, SID, H>>,
+    ) {
+        self.reliable_broadcast_fault_logs
+            .insert(node_id, fault_logs);
+    }
+
+    pub fn as_reliable_broadcast_fault_logs(
+        &self,
+    ) -> &BTreeMap<ID, Vec<reliable_broadcast::FaultLog<ID, SID, H>>> {
+        &self.reliable_broadcast_fault_logs
+    }
+
+    pub(crate) fn try_add_received_coin_share(
+        &mut self,
+        value: SignatureShare,
+        sender_id: ID,
+    ) -> Option<SignatureShare> {
+        match self.received_coin_shares.entry(sender_id) {
+            Entry::Occupied(_) => Some(value),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn has_received_coin_share(&self, sender_id: &ID) -> bool {
+        self.received_coin_shares.contains_key(sender_id)
+    }
+
+    pub(crate) fn get_total_received_coin_shares_count(&self) -> usize {
+        self.received_coin_shares.len()
+    }
+
+    pub(crate) fn get_received_coin_shares<IDX: binary_agreement::validator::ValidatorIndex>(
+        &self,
+        validator_set: &binary_agreement::validator::ValidatorSet<ID, IDX>,
+    ) -> Vec<(u64, &SignatureShare)> {
+        self.received_coin_shares
+            .iter()
+            .map(|(node_id, share)| {
+                let index: u64 = validator_set.index(node_id).unwrap().into();
+                (index, share)
+            })
+            .collect()
+    }
+
+    /// The order in which the binary agreement instances are run.
+    pub fn as_permutation(&self) -> &Vec<ID> {
+        &self.permutation
+    }
+
+    pub(crate) fn set_permutation(&mut self, permutation: Vec<ID>) {
+        self.permutation = permutation;
+    }
+
+    /// The round of binary agreement instances that decided, or that was running when the
+    /// procedure stopped. Every instance of a round decides 0 before the next round starts.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub(crate) fn set_round(&mut self, round: usize) {
+        self.round = round;
+    }
+
+    /// The outputs of the binary agreement instances of the latest round.
+    pub fn as_binary_agreement_outputs(&self) -> &BTreeMap<ID, Option<bool>> {
+        &self.binary_agreement_outputs
+    }
+
+    pub(crate) fn get_binary_agreement_output(&self, node_id: &ID) -> Option<bool> {
+        self.binary_agreement_outputs
+            .get(node_id)
+            .copied()
+            .flatten()
+    }
+
+    pub(crate) fn set_binary_agreement_state<
+        IDX: binary_agreement::validator::ValidatorIndex,
+        BSID: binary_agreement::session::SessionId,
+    >(
+        &mut self,
+        node_id: ID,
+        state: BinaryAgreementState<ID, IDX, BSID>,
+    ) {
+        let (output, fault_logs) = state.into_output_and_logs();
+        self.binary_agreement_outputs
+            .insert(node_id.clone(), output);
+        self.binary_agreement_fault_logs
+            .entry(node_id)
+            .or_default()
+            .extend(fault_logs);
+    }
+
+    pub fn as_binary_agreement_fault_logs(
+        &self,
+    ) -> &BTreeMap<ID, Vec<binary_agreement::FaultLog<ID>>> {
+        &self.binary_agreement_fault_logs
+    }
+
+    pub fn fault_logs(&self) -> &Vec<FaultLog<ID>> {
+        &self.fault_logs
+    }
+
+    pub(crate) fn push_fault_log(&mut self, fault_log: FaultLog<ID>) {
+        self.fault_logs.push(fault_log);
+    }
+}
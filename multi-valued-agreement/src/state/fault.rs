@@ -0,0 +1,29 @@
+use crate::{message::PermutationCoinMessage, node::NodeId};
+
+#[derive(Debug, Clone)]
+pub enum FaultLog<NID: NodeId> {
+    PermutationCoin(PermutationCoinFaultLog<NID>),
+    ReliableBroadcast(ReliableBroadcastFaultLog<NID>),
+}
+
+#[derive(Debug, Clone)]
+pub enum FaultType {
+    UnknownSender,
+    DuplicateCoinShare,
+    InvalidSignatureShare,
+}
+
+#[derive(Debug, Clone)]
+pub struct PermutationCoinFaultLog<NID: NodeId> {
+    pub sender_id: NID,
+    pub message: Box<PermutationCoinMessage>,
+    pub fault_type: FaultType,
+}
+
+/// The reliable broadcast of a proposer failed, e.g. because the shards it sent don't decode to
+/// the value it committed to. Its value is treated as undelivered.
+#[derive(Debug, Clone)]
+pub struct ReliableBroadcastFaultLog<NID: NodeId> {
+    pub proposer_id: NID,
+    pub cause: String,
+}
@@ -0,0 +1,3 @@
+mod index;
+
+pub use index::*;
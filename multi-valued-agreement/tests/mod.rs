@@ -0,0 +1,458 @@
+use binary_agreement::{
+    epoch::Epoch,
+    message::{BinaryAgreementMessage, BinaryAgreementMessageContent},
+    node::NodeMessage as BaNodeMessage,
+    BinaryAgreement,
+};
+use logger::prelude::*;
+use multi_valued_agreement::{
+    message::PermutationCoinMessage, node::NodeMessage as MvbaNodeMessage, MultiValuedAgreement,
+};
+use rand::thread_rng;
+use reliable_broadcast::{
+    merkle::Sha3Hasher,
+    message::{BroadcastMessage, BroadcastMessageContent},
+    node::NodeMessage as RbcNodeMessage,
+    ReliableBroadcast,
+};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::{fmt, thread};
+use threshold_crypto::{SecretKeyShare, SecretKeyShares};
+
+type NodeId = u16;
+
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug, Hash)]
+struct Index(u64);
+
+impl From<u16> for Index {
+    fn from(value: u16) -> Self {
+        Self(value as u64)
+    }
+}
+
+impl From<usize> for Index {
+    fn from(value: usize) -> Self {
+        Self(value as u64)
+    }
+}
+
+impl From<Index> for u64 {
+    fn from(value: Index) -> Self {
+        value.0
+    }
+}
+
+impl From<Index> for usize {
+    fn from(value: Index) -> Self {
+        value.0 as usize
+    }
+}
+
+impl AsRef<u64> for Index {
+    fn as_ref(&self) -> &u64 {
+        &self.0
+    }
+}
+
+impl fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+type SessionId = String;
+
+type RbcReceiver = Arc<Mutex<Receiver<RbcNodeMessage<NodeId, SessionId>>>>;
+type RbcSender = SyncSender<RbcNodeMessage<NodeId, SessionId>>;
+type BaReceiver = Arc<Mutex<Receiver<BaNodeMessage<NodeId>>>>;
+type BaSender = SyncSender<BaNodeMessage<NodeId>>;
+
+struct ReliableBroadcastImpl {
+    id: NodeId,
+    target_id: NodeId,
+    message_receiver: RbcReceiver,
+    message_router: BTreeMap<NodeId, RbcSender>,
+}
+
+impl fmt::Debug for ReliableBroadcastImpl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.id, self.target_id)
+    }
+}
+
+impl ReliableBroadcast for ReliableBroadcastImpl {
+    type NodeId = NodeId;
+    type ValidatorIndex = Index;
+    type SessionId = SessionId;
+    type Hasher = Sha3Hasher;
+
+    fn my_id(&self) -> &NodeId {
+        &self.id
+    }
+
+    fn next_message(&self) -> RbcNodeMessage<NodeId, SessionId> {
+        let receiver = self.message_receiver.lock().unwrap();
+        let message = receiver.recv().unwrap();
+        let sender_id = match &message {
+            RbcNodeMessage::BroadcastMessage {
+                sender_id,
+                message: _,
+            } => sender_id,
+            RbcNodeMessage::Terminate => self.my_id(),
+        };
+        debug!("[receive message]{sender_id} -> {self:?}");
+        message
+    }
+
+    fn send_message(&self, target_id: NodeId, message: BroadcastMessage<SessionId>) {
+        assert_ne!(self.id, target_id);
+        let message_type = match message.content {
+            BroadcastMessageContent::Value(_) => "value message",
+            BroadcastMessageContent::Echo(_) => "echo message",
+            BroadcastMessageContent::Ready(_) => "ready message",
+            BroadcastMessageContent::PlainValue(_) => "plain value message",
+            BroadcastMessageContent::PlainEcho(_) => "plain echo message",
+            BroadcastMessageContent::EchoHash(_) => "echo hash message",
+            BroadcastMessageContent::CanDecode(_) => "can decode message",
+            BroadcastMessageContent::ShardRequest(_) => "shard request message",
+            BroadcastMessageContent::ShardResponse(_) => "shard response message",
+        };
+        debug!(
+            "[send message]{self:?} -> {target_id}-{}: {message_type}",
+            self.id
+        );
+        let sender = self.message_router.get(&target_id).unwrap();
+        sender
+            .send(RbcNodeMessage::BroadcastMessage {
+                sender_id: self.id,
+                message,
+            })
+            .expect("message should be sent without error...");
+    }
+}
+
+struct BinaryAgreementImpl {
+    id: NodeId,
+    target_id: NodeId,
+    message_receiver: BaReceiver,
+    message_router: BTreeMap<NodeId, BaSender>,
+    message_queue: BTreeMap<Epoch, VecDeque<BaNodeMessage<NodeId>>>,
+}
+
+impl fmt::Debug for BinaryAgreementImpl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.id, self.target_id)
+    }
+}
+
+impl BinaryAgreement for BinaryAgreementImpl {
+    type NodeId = NodeId;
+    type ValidatorIndex = Index;
+    type SessionId = SessionId;
+
+    fn my_id(&self) -> &Self::NodeId {
+        &self.id
+    }
+
+    fn next_message(&mut self, epoch: &Epoch) -> BaNodeMessage<NodeId> {
+        if let Some(queue) = self.message_queue.get_mut(epoch) {
+            if let Some(queued_message) = queue.pop_front() {
+                return queued_message;
+            }
+        }
+        loop {
+            let message = self.message_receiver.lock().unwrap().recv().unwrap();
+            match &message {
+                BaNodeMessage::BinaryAgreementMessage {
+                    sender_id,
+                    message:
+                        BinaryAgreementMessage {
+                            epoch: message_epoch,
+                            content: _,
+                        },
+                } => {
+                    debug!("[receive message]{sender_id} -> {}", self.id);
+                    if message_epoch == epoch {
+                        return message;
+                    } else {
+                        let queue = self.message_queue.entry(*message_epoch).or_default();
+                        queue.push_back(message);
+                    }
+                }
+                BaNodeMessage::Terminate => {
+                    return message;
+                }
+            };
+        }
+    }
+
+    fn send_message(&self, target_id: Self::NodeId, message: BinaryAgreementMessage) {
+        let message_type = match message.content {
+            BinaryAgreementMessageContent::BVal(_) => "BVal",
+            BinaryAgreementMessageContent::Aux(_) => "AUX",
+            BinaryAgreementMessageContent::Conf(_) => "CONF",
+            BinaryAgreementMessageContent::Coin(_) => "COIN",
+            BinaryAgreementMessageContent::Prop(_) => "PROP",
+            BinaryAgreementMessageContent::PropAux(_) => "PROP_AUX",
+        };
+        debug!(
+            "[send message]{} -> {target_id}: {:?} {message_type}",
+            self.id, message.epoch
+        );
+        let sender = self.message_router.get(&target_id).unwrap();
+        sender
+            .send(BaNodeMessage::BinaryAgreementMessage {
+                sender_id: self.id,
+                message,
+            })
+            .expect("message should be sent without error...");
+    }
+
+    fn on_next_epoch(&mut self, epoch: &Epoch) {
+        debug!("[begin epoch]{self:?}: {epoch:?}");
+    }
+}
+
+struct TestNode {
+    id: NodeId,
+    index: Index,
+    rbc_message_receivers: BTreeMap<NodeId, RbcReceiver>,
+    rbc_message_router: BTreeMap<NodeId, BTreeMap<NodeId, RbcSender>>,
+    ba_message_receivers: BTreeMap<NodeId, BaReceiver>,
+    ba_message_router: BTreeMap<NodeId, BTreeMap<NodeId, BaSender>>,
+    coin_message_receiver: Receiver<MvbaNodeMessage<NodeId>>,
+    coin_message_router: BTreeMap<NodeId, SyncSender<MvbaNodeMessage<NodeId>>>,
+}
+
+impl fmt::Debug for TestNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl MultiValuedAgreement for TestNode {
+    type NodeId = NodeId;
+    type ValidatorIndex = Index;
+    type SessionId = SessionId;
+    type Hasher = Sha3Hasher;
+    type ReliableBroadcast = ReliableBroadcastImpl;
+    type BinaryAgreement = BinaryAgreementImpl;
+
+    fn my_id(&self) -> &Self::NodeId {
+        &self.id
+    }
+
+    fn create_reliable_broadcast_instance(
+        &mut self,
+        target_id: &NodeId,
+    ) -> Self::ReliableBroadcast {
+        debug!("RBC instance created: {}-{target_id}", self.id);
+        let message_receiver = self.rbc_message_receivers.get(target_id).unwrap().clone();
+        let mut message_router = BTreeMap::default();
+        for (mvba_node_id, senders) in &self.rbc_message_router {
+            for (rbc_instance_id, sender) in senders {
+                if rbc_instance_id == target_id {
+                    message_router.insert(*mvba_node_id, sender.clone());
+                }
+            }
+        }
+        ReliableBroadcastImpl {
+            id: self.id,
+            target_id: *target_id,
+            message_receiver,
+            message_router,
+        }
+    }
+
+    fn terminate_reliable_broadcast(&self, target_id: &Self::NodeId) {
+        debug!("let's terminate RBC instance: {}-{target_id}", self.id);
+        let sender = self
+            .rbc_message_router
+            .get(&self.id)
+            .unwrap()
+            .get(target_id)
+            .unwrap();
+        sender
+            .send(RbcNodeMessage::Terminate)
+            .expect("rbc terminate message should be sent without error...");
+    }
+
+    fn get_reliable_broadcast_session_id(&self, target_id: &NodeId) -> Self::SessionId {
+        format!("test-rbc-{target_id}")
+    }
+
+    fn create_binary_agreement_instance(
+        &mut self,
+        target_id: &NodeId,
+        round: usize,
+    ) -> Self::BinaryAgreement {
+        debug!("BA instance created: {}-{target_id} round {round}", self.id);
+        let message_receiver = self.ba_message_receivers.get(target_id).unwrap().clone();
+        let mut message_router = BTreeMap::default();
+        for (mvba_node_id, senders) in &self.ba_message_router {
+            for (rbc_instance_id, sender) in senders {
+                if rbc_instance_id == target_id {
+                    message_router.insert(*mvba_node_id, sender.clone());
+                }
+            }
+        }
+        BinaryAgreementImpl {
+            id: self.id,
+            target_id: *target_id,
+            message_receiver,
+            message_router,
+            message_queue: BTreeMap::new(),
+        }
+    }
+
+    fn get_binary_agreement_session_id(&self, target_id: &NodeId, round: usize) -> Self::SessionId {
+        format!("test-{target_id}-{round}")
+    }
+
+    fn get_permutation_session_id(&self) -> Self::SessionId {
+        "test-permutation".to_owned()
+    }
+
+    fn next_message(&self) -> MvbaNodeMessage<NodeId> {
+        self.coin_message_receiver.recv().unwrap()
+    }
+
+    fn send_message(&self, target_id: NodeId, message: PermutationCoinMessage) {
+        debug!("[send message]{} -> {target_id}: permutation coin", self.id);
+        let sender = self.coin_message_router.get(&target_id).unwrap();
+        sender
+            .send(MvbaNodeMessage::PermutationCoinMessage {
+                sender_id: self.id,
+                message: Box::new(message),
+            })
+            .expect("message should be sent without error...");
+    }
+}
+
+fn gen_random_secret_key_shares(threshold: usize) -> SecretKeyShares {
+    let mut rnd = thread_rng();
+    SecretKeyShares::random(threshold, &mut rnd)
+}
+
+#[test]
+fn test_simple_procedure() {
+    // init logger
+    let mut builder = logger::default::DefaultLoggerBuilder::new();
+    builder.is_async(true);
+    builder.level(logger::Level::Debug);
+    let _logger = builder.build();
+
+    let channel_size = 10000;
+    // { mvba_node_id: { target_index: (rbc_receiver, ba_receiver) } }
+    let mut message_receivers: BTreeMap<NodeId, BTreeMap<NodeId, (RbcReceiver, BaReceiver)>> =
+        BTreeMap::new();
+    // { mvba_index: { send_target_node_id: sender } }
+    let mut rbc_message_router: BTreeMap<NodeId, BTreeMap<NodeId, RbcSender>> = BTreeMap::new();
+    // { mvba_index: { send_target_node_id: sender } }
+    let mut ba_message_router: BTreeMap<NodeId, BTreeMap<NodeId, BaSender>> = BTreeMap::new();
+    for id in 1..=4 {
+        message_receivers.insert(id, BTreeMap::default());
+        rbc_message_router.insert(id, BTreeMap::default());
+        ba_message_router.insert(id, BTreeMap::default());
+        for child_id in 1..=4 {
+            let (rb_sender, rb_receiver) = sync_channel(channel_size);
+            let (ba_sender, ba_receiver) = sync_channel(channel_size);
+            message_receivers.get_mut(&id).unwrap().insert(
+                child_id,
+                (
+                    Arc::new(Mutex::new(rb_receiver)),
+                    Arc::new(Mutex::new(ba_receiver)),
+                ),
+            );
+            rbc_message_router
+                .get_mut(&id)
+                .unwrap()
+                .insert(child_id, rb_sender);
+            ba_message_router
+                .get_mut(&id)
+                .unwrap()
+                .insert(child_id, ba_sender);
+        }
+    }
+    let mut coin_message_receivers = BTreeMap::new();
+    let mut coin_message_router = BTreeMap::new();
+    for id in 1..=4 {
+        let (coin_sender, coin_receiver) = sync_channel(channel_size);
+        coin_message_receivers.insert(id, coin_receiver);
+        coin_message_router.insert(id, coin_sender);
+    }
+    let mut nodes: BTreeMap<NodeId, TestNode> = BTreeMap::new();
+    for (id, receivers) in message_receivers {
+        let index: Index = (id - 1).into();
+        let mut rbc_message_receivers = BTreeMap::new();
+        let mut ba_message_receivers = BTreeMap::new();
+        for (child_index, (rbc_receiver, ba_receiver)) in receivers {
+            rbc_message_receivers.insert(child_index, rbc_receiver);
+            ba_message_receivers.insert(child_index, ba_receiver);
+        }
+        nodes.insert(
+            id,
+            TestNode {
+                id,
+                index,
+                rbc_message_receivers,
+                rbc_message_router: rbc_message_router.clone(),
+                ba_message_receivers,
+                ba_message_router: ba_message_router.clone(),
+                coin_message_receiver: coin_message_receivers.remove(&id).unwrap(),
+                coin_message_router: coin_message_router.clone(),
+            },
+        );
+    }
+
+    let inputs: Vec<&str> = vec!["Foo1", "Foo2", "Foo3", "Foo4"];
+    let mut handles = BTreeMap::new();
+    let validator_indices: BTreeMap<NodeId, Index> =
+        nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+    let threshold = (nodes.len() - 1) / 3;
+    let secret_key_shares = gen_random_secret_key_shares(threshold);
+    let public_key_shares = secret_key_shares.public_keys();
+    let secret_key_share_map: BTreeMap<NodeId, SecretKeyShare> = validator_indices
+        .iter()
+        .map(|(node_id, index)| {
+            let secret_key_share = secret_key_shares.secret_key_share(*index.as_ref());
+            (*node_id, secret_key_share)
+        })
+        .collect();
+    for (id, mut node) in nodes {
+        let input = inputs.get((id - 1) as usize).unwrap().as_bytes().to_vec();
+        let validator_indices = validator_indices.clone();
+        let secret_key_share_map = secret_key_share_map.clone();
+        let public_key_shares = public_key_shares.clone();
+        let handle = thread::spawn(move || {
+            let secret_key_share = secret_key_share_map.get(&id).unwrap().clone();
+            node.propose(
+                input,
+                validator_indices,
+                secret_key_share,
+                public_key_shares,
+            )
+        });
+        handles.insert(id, handle);
+    }
+    let mut agreed_outputs = Vec::new();
+    for (id, handle) in handles {
+        let result = handle.join().unwrap();
+        match result {
+            Ok(state) => {
+                let (proposer_id, output) = state.as_output().unwrap().clone();
+                let output = std::str::from_utf8(&output).unwrap().to_owned();
+                println!("mvba {id} got output from {proposer_id}: {output}");
+                assert_eq!(output, inputs[(proposer_id - 1) as usize]);
+                agreed_outputs.push(output);
+            }
+            Err(err) => {
+                panic!("id: {id:?}, err: {err:?}");
+            }
+        }
+    }
+    agreed_outputs.dedup();
+    assert_eq!(agreed_outputs.len(), 1);
+}
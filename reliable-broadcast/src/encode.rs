@@ -1,31 +1,153 @@
 use crate::Result;
-use reed_solomon_erasure::galois_8::ReedSolomon;
+use reed_solomon_erasure::{galois_16, galois_8};
+use std::sync::Arc;
 
+/// Maximum total number of shards supported by Reed-Solomon over GF(2^8).
+pub const MAX_GALOIS_8_SHARD_COUNT: usize = 256;
+
+/// Reed-Solomon erasure coder. Committees of up to `MAX_GALOIS_8_SHARD_COUNT` validators are
+/// coded over GF(2^8); larger ones over GF(2^16), whose shards consist of 2-byte elements.
+///
+/// The backends are shared rather than cloned, as cloning rebuilds their coding matrix.
 #[derive(Debug, Clone)]
-pub struct Coder(Box<ReedSolomon>);
+pub enum Coder {
+    Galois8(Arc<galois_8::ReedSolomon>),
+    Galois16(Arc<galois_16::ReedSolomon>),
+}
 
 impl Coder {
     pub fn new(data_shards: usize, parity_shards: usize) -> Result<Self> {
-        let reed_solomon = ReedSolomon::new(data_shards, parity_shards)?;
-        Ok(Self(Box::new(reed_solomon)))
+        if data_shards + parity_shards <= MAX_GALOIS_8_SHARD_COUNT {
+            let reed_solomon = galois_8::ReedSolomon::new(data_shards, parity_shards)?;
+            Ok(Self::Galois8(Arc::new(reed_solomon)))
+        } else {
+            let reed_solomon = galois_16::ReedSolomon::new(data_shards, parity_shards)?;
+            Ok(Self::Galois16(Arc::new(reed_solomon)))
+        }
     }
 
     /// Returns the number of data shards.
     pub fn data_shard_count(&self) -> usize {
-        self.0.data_shard_count()
+        match self {
+            Self::Galois8(reed_solomon) => reed_solomon.data_shard_count(),
+            Self::Galois16(reed_solomon) => reed_solomon.data_shard_count(),
+        }
     }
 
     /// Returns the number of parity shards.
     pub fn parity_shard_count(&self) -> usize {
-        self.0.parity_shard_count()
+        match self {
+            Self::Galois8(reed_solomon) => reed_solomon.parity_shard_count(),
+            Self::Galois16(reed_solomon) => reed_solomon.parity_shard_count(),
+        }
+    }
+
+    /// Returns the size in bytes of a field element. Every shard length must be a multiple of it.
+    pub fn element_size(&self) -> usize {
+        match self {
+            Self::Galois8(_) => 1,
+            Self::Galois16(_) => 2,
+        }
+    }
+
+    /// Returns the length of the shards a value of `value_len` bytes is split into: the value
+    /// length divided by the number of data shards and rounded up to a whole number of field
+    /// elements, so that the full value always fits in the data shards.
+    pub fn shard_len(&self, value_len: usize) -> usize {
+        let data_shard_count = self.data_shard_count();
+        let element_size = self.element_size();
+        let shard_len = (value_len + data_shard_count - 1) / data_shard_count;
+        (shard_len + element_size - 1) / element_size * element_size
     }
 
     /// Constructs (and overwrites) the parity shards.
     pub fn encode(&self, shards: &mut [&mut [u8]]) -> Result<()> {
-        Ok(self.0.encode(shards)?)
+        match self {
+            Self::Galois8(reed_solomon) => Ok(reed_solomon.encode(shards)?),
+            Self::Galois16(reed_solomon) => {
+                let mut elements = shards
+                    .iter()
+                    .map(|shard| to_elements(shard))
+                    .collect::<Result<Vec<_>>>()?;
+                reed_solomon.encode(&mut elements)?;
+                for (shard, elements) in shards.iter_mut().zip(elements) {
+                    shard.copy_from_slice(&from_elements(&elements));
+                }
+                Ok(())
+            }
+        }
     }
 
     pub fn reconstruct(&self, shards: &mut [Option<Box<[u8]>>]) -> Result<()> {
-        Ok(self.0.reconstruct(shards)?)
+        match self {
+            Self::Galois8(reed_solomon) => Ok(reed_solomon.reconstruct(shards)?),
+            Self::Galois16(reed_solomon) => {
+                let mut elements = shards
+                    .iter()
+                    .map(|shard| shard.as_deref().map(to_elements).transpose())
+                    .collect::<Result<Vec<_>>>()?;
+                reed_solomon.reconstruct(&mut elements)?;
+                for (shard, elements) in shards.iter_mut().zip(elements) {
+                    *shard = elements.map(|elements| from_elements(&elements).into_boxed_slice());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Splits a shard into GF(2^16) elements. Fails if its length is odd.
+fn to_elements(shard: &[u8]) -> Result<Vec<[u8; 2]>> {
+    if shard.len() % 2 != 0 {
+        return Err(reed_solomon_erasure::Error::IncorrectShardSize.into());
+    }
+    Ok(shard
+        .chunks_exact(2)
+        .map(|chunk| [chunk[0], chunk[1]])
+        .collect())
+}
+
+fn from_elements(elements: &[[u8; 2]]) -> Vec<u8> {
+    elements.iter().flatten().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_galois_16_is_used_for_large_committees() {
+        assert!(matches!(Coder::new(86, 170).unwrap(), Coder::Galois8(_)));
+        assert!(matches!(Coder::new(87, 170).unwrap(), Coder::Galois16(_)));
+    }
+
+    #[test]
+    fn test_galois_16_encode_and_reconstruct() {
+        let coder = Coder::new(100, 200).unwrap();
+        assert_eq!(2, coder.element_size());
+
+        let mut value: Vec<u8> = (0..300 * 4).map(|i| i as u8).collect();
+        value[100 * 4..].iter_mut().for_each(|byte| *byte = 0);
+        let mut shards: Vec<&mut [u8]> = value.chunks_mut(4).collect();
+        coder.encode(&mut shards).unwrap();
+
+        let expected: Vec<Vec<u8>> = shards.iter().map(|shard| shard.to_vec()).collect();
+        let mut shards: Vec<Option<Box<[u8]>>> = expected
+            .iter()
+            .enumerate()
+            .map(|(i, shard)| (i % 3 == 0).then(|| shard.clone().into_boxed_slice()))
+            .collect();
+        coder.reconstruct(&mut shards).unwrap();
+        for (shard, expected) in shards.into_iter().zip(expected) {
+            assert_eq!(Some(expected.into_boxed_slice()), shard);
+        }
+    }
+
+    #[test]
+    fn test_galois_16_rejects_odd_shards() {
+        let coder = Coder::new(100, 200).unwrap();
+        let mut value = vec![0u8; 300 * 3];
+        let mut shards: Vec<&mut [u8]> = value.chunks_mut(3).collect();
+        assert!(coder.encode(&mut shards).is_err());
     }
 }
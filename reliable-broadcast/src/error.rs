@@ -11,6 +11,9 @@ pub enum Error {
 
     #[error("Computed merkle tree root hash is invalid.")]
     IllegalMerkleTreeRootHash,
+
+    #[error("Payload of {size} bytes exceeds the maximum size of {max_size} bytes.")]
+    PayloadTooLarge { size: usize, max_size: usize },
 }
 
 impl From<reed_solomon_erasure::Error> for Error {
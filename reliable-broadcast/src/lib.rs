@@ -5,7 +5,9 @@ pub type Result<T> = core::result::Result<T, Error>;
 pub mod encode;
 pub mod merkle;
 pub mod message;
+pub mod mode;
 pub mod node;
+pub mod session;
 pub mod validator;
 
 mod state;
@@ -1,8 +1,90 @@
 pub(crate) mod hasher;
 mod merkle_tree;
 mod proof;
+mod version;
 
+#[cfg(feature = "blake3")]
+pub use hasher::Blake3Hasher;
+#[cfg(feature = "sha2")]
+pub use hasher::Sha256Hasher;
+pub use hasher::{MerkleHasher, Sha3Hasher};
 pub use merkle_tree::MerkleTree;
 pub use proof::Proof;
+pub use version::MerkleTreeVersion;
 
-pub type Digest = [u8; 32];
+pub type Digest<H = Sha3Hasher> = <H as MerkleHasher>::Digest;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_proofs_are_valid<H: MerkleHasher>() {
+        let values: Vec<Vec<u8>> = (0..7u8).map(|i| vec![i; 3]).collect();
+        let tree: MerkleTree<_, H> = MerkleTree::new(values.clone());
+        for index in 0..values.len() {
+            let proof = tree.proof(index).unwrap();
+            assert!(proof.validate(values.len()));
+            assert_eq!(tree.root_hash(), proof.root_hash());
+        }
+    }
+
+    #[test]
+    fn test_sha3_proofs() {
+        assert_proofs_are_valid::<Sha3Hasher>();
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3_proofs() {
+        assert_proofs_are_valid::<Blake3Hasher>();
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_sha256_proofs() {
+        assert_proofs_are_valid::<Sha256Hasher>();
+    }
+
+    /// Returns a 2-leaf tree and a 1-leaf tree whose leaf is the concatenation of the other
+    /// tree's leaf hashes.
+    fn second_preimage_trees(
+        version: MerkleTreeVersion,
+    ) -> (MerkleTree<Vec<u8>>, MerkleTree<Vec<u8>>) {
+        let tree = MerkleTree::with_version(vec![b"a".to_vec(), b"b".to_vec()], version);
+        let leaf: Vec<u8> = [b"a", b"b"]
+            .iter()
+            .flat_map(|value| version.hash_leaf::<Sha3Hasher, _>(value))
+            .collect();
+        (tree, MerkleTree::with_version(vec![leaf], version))
+    }
+
+    #[test]
+    fn test_leaf_and_interior_hashes_are_separated() {
+        let (tree, forged) = second_preimage_trees(MerkleTreeVersion::V1);
+        assert_ne!(tree.root_hash(), forged.root_hash());
+        assert!(!forged.proof(0).unwrap().validate(2));
+    }
+
+    #[cfg(feature = "legacy-merkle-tree")]
+    #[test]
+    fn test_legacy_trees_are_readable() {
+        let (tree, forged) = second_preimage_trees(MerkleTreeVersion::Legacy);
+        // the ambiguity the prefixes are there to prevent
+        assert_eq!(tree.root_hash(), forged.root_hash());
+        for index in 0..2 {
+            let proof = tree.proof(index).unwrap();
+            assert_eq!(MerkleTreeVersion::Legacy, proof.version());
+            assert!(proof.validate_with_version(2, MerkleTreeVersion::Legacy));
+        }
+    }
+
+    #[cfg(feature = "legacy-merkle-tree")]
+    #[test]
+    fn test_proof_version_is_not_downgraded() {
+        let (tree, _) = second_preimage_trees(MerkleTreeVersion::Legacy);
+        let proof = tree.proof(0).unwrap();
+        assert!(proof.validate_with_version(2, MerkleTreeVersion::Legacy));
+        assert!(!proof.validate(2));
+        assert!(!proof.validate_with_version(2, MerkleTreeVersion::V1));
+    }
+}
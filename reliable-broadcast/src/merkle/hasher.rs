@@ -1,28 +1,82 @@
-use super::Digest;
+use core::{fmt, hash};
 use tiny_keccak::{Hasher, Sha3};
 
-/// Takes a chunk of one or two digests. In the former case, returns the digest itself, in the
-/// latter, it returns the hash of the two digests.
-pub(crate) fn hash_chunk(chunk: &[Digest]) -> Digest {
-    if chunk.len() == 1 {
-        chunk[0]
-    } else {
-        hash_pair(&chunk[0], &chunk[1])
+/// The hash function of a Merkle tree, chosen per deployment.
+pub trait MerkleHasher:
+    Clone + Copy + Default + Eq + PartialEq + fmt::Debug + Send + Sync + 'static
+{
+    type Digest: Clone
+        + Copy
+        + Eq
+        + Ord
+        + hash::Hash
+        + fmt::Debug
+        + AsRef<[u8]>
+        + Send
+        + Sync
+        + 'static;
+
+    /// Returns the hash of the value's `[u8]` representation.
+    fn hash<T: AsRef<[u8]>>(value: T) -> Self::Digest;
+
+    /// Returns the hash of the concatenated bytes of `d0` and `d1`.
+    fn hash_pair<T0: AsRef<[u8]>, T1: AsRef<[u8]>>(v0: &T0, v1: &T1) -> Self::Digest {
+        let bytes: Vec<u8> = v0.as_ref().iter().chain(v1.as_ref()).cloned().collect();
+        Self::hash(&bytes)
+    }
+}
+
+/// SHA3-256, the default hash function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sha3Hasher;
+
+impl MerkleHasher for Sha3Hasher {
+    type Digest = [u8; 32];
+
+    fn hash<T: AsRef<[u8]>>(value: T) -> Self::Digest {
+        let mut sha3 = Sha3::v256();
+        sha3.update(value.as_ref());
+
+        let mut out = [0u8; 32];
+        sha3.finalize(&mut out);
+        out
     }
 }
 
-/// Returns the SHA-256 hash of the value's `[u8]` representation.
-pub(crate) fn hash<T: AsRef<[u8]>>(value: T) -> Digest {
-    let mut sha3 = Sha3::v256();
-    sha3.update(value.as_ref());
+/// BLAKE3 with 256-bit output, for throughput.
+#[cfg(feature = "blake3")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Blake3Hasher;
 
-    let mut out = [0u8; 32];
-    sha3.finalize(&mut out);
-    out
+#[cfg(feature = "blake3")]
+impl MerkleHasher for Blake3Hasher {
+    type Digest = [u8; 32];
+
+    fn hash<T: AsRef<[u8]>>(value: T) -> Self::Digest {
+        *blake3::hash(value.as_ref()).as_bytes()
+    }
+}
+
+/// SHA-256, for interoperability with systems built on SHA-2.
+#[cfg(feature = "sha2")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sha256Hasher;
+
+#[cfg(feature = "sha2")]
+impl MerkleHasher for Sha256Hasher {
+    type Digest = [u8; 32];
+
+    fn hash<T: AsRef<[u8]>>(value: T) -> Self::Digest {
+        use sha2::Digest as _;
+        sha2::Sha256::digest(value.as_ref()).into()
+    }
 }
 
-/// Returns the hash of the concatenated bytes of `d0` and `d1`.
-pub(crate) fn hash_pair<T0: AsRef<[u8]>, T1: AsRef<[u8]>>(v0: &T0, v1: &T1) -> Digest {
-    let bytes: Vec<u8> = v0.as_ref().iter().chain(v1.as_ref()).cloned().collect();
-    hash(&bytes)
+/// Prefixed to plain mode values before hashing, so that their digests never collide with the
+/// root hash of a Merkle tree.
+const PLAIN_VALUE_PREFIX: &[u8] = b"reliable-broadcast/plain-value";
+
+/// Returns the digest a plain mode value is referred to by.
+pub(crate) fn hash_plain_value<H: MerkleHasher, T: AsRef<[u8]>>(value: T) -> H::Digest {
+    H::hash_pair(&PLAIN_VALUE_PREFIX, &value)
 }
@@ -1,20 +1,44 @@
-use super::{hasher, Digest, Proof};
+use super::{Digest, MerkleHasher, MerkleTreeVersion, Proof, Sha3Hasher};
 use core::{fmt, mem};
 use hex_fmt::HexFmt;
 
-pub struct MerkleTree<T: AsRef<[u8]>> {
-    levels: Vec<Vec<Digest>>,
+pub struct MerkleTree<T: AsRef<[u8]>, H: MerkleHasher = Sha3Hasher> {
+    levels: Vec<Vec<Digest<H>>>,
     values: Vec<T>,
-    root_hash: Digest,
+    root_hash: Digest<H>,
+    version: MerkleTreeVersion,
 }
 
-impl<T: AsRef<[u8]> + Clone> MerkleTree<T> {
+impl<T: AsRef<[u8]> + Clone, H: MerkleHasher> MerkleTree<T, H> {
     pub fn new(values: Vec<T>) -> Self {
         values.into()
     }
 
+    /// Builds the tree in the given format. `MerkleTree::new` always uses the latest one.
+    pub fn with_version(values: Vec<T>, version: MerkleTreeVersion) -> Self {
+        let mut levels = Vec::new();
+        let mut cur_lvl: Vec<Digest<H>> = values
+            .iter()
+            .map(|value| version.hash_leaf::<H, _>(value))
+            .collect();
+        while cur_lvl.len() > 1 {
+            let next_lvl = cur_lvl
+                .chunks(2)
+                .map(|chunk| version.hash_chunk::<H>(chunk))
+                .collect();
+            levels.push(mem::replace(&mut cur_lvl, next_lvl));
+        }
+        let root_hash = cur_lvl[0];
+        MerkleTree {
+            levels,
+            values,
+            root_hash,
+            version,
+        }
+    }
+
     /// Returns the proof for entry `index`, if that is a valid index.
-    pub fn proof(&self, index: usize) -> Option<Proof<T>> {
+    pub fn proof(&self, index: usize) -> Option<Proof<T, H>> {
         let value = self.values.get(index)?.clone();
         let mut lvl_i = index;
         let mut digests = Vec::new();
@@ -25,14 +49,25 @@ impl<T: AsRef<[u8]> + Clone> MerkleTree<T> {
             }
             lvl_i /= 2;
         }
-        Some(Proof::new(value, index, digests, self.root_hash))
+        Some(Proof::new(
+            value,
+            index,
+            digests,
+            self.root_hash,
+            self.version,
+        ))
     }
 
     /// Returns the root hash of the tree.
-    pub fn root_hash(&self) -> &Digest {
+    pub fn root_hash(&self) -> &Digest<H> {
         &self.root_hash
     }
 
+    /// Returns the format the tree has been built in.
+    pub fn version(&self) -> MerkleTreeVersion {
+        self.version
+    }
+
     /// Returns a the slice containing all leaf values.
     pub fn values(&self) -> &[T] {
         &self.values
@@ -44,24 +79,13 @@ impl<T: AsRef<[u8]> + Clone> MerkleTree<T> {
     }
 }
 
-impl<T: AsRef<[u8]>> From<Vec<T>> for MerkleTree<T> {
+impl<T: AsRef<[u8]> + Clone, H: MerkleHasher> From<Vec<T>> for MerkleTree<T, H> {
     fn from(values: Vec<T>) -> Self {
-        let mut levels = Vec::new();
-        let mut cur_lvl: Vec<Digest> = values.iter().map(hasher::hash).collect();
-        while cur_lvl.len() > 1 {
-            let next_lvl = cur_lvl.chunks(2).map(hasher::hash_chunk).collect();
-            levels.push(mem::replace(&mut cur_lvl, next_lvl));
-        }
-        let root_hash = cur_lvl[0];
-        MerkleTree {
-            levels,
-            values,
-            root_hash,
-        }
+        Self::with_version(values, MerkleTreeVersion::default())
     }
 }
 
-impl<T: AsRef<[u8]>> fmt::Debug for MerkleTree<T> {
+impl<T: AsRef<[u8]>, H: MerkleHasher> fmt::Debug for MerkleTree<T, H> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
@@ -1,29 +1,49 @@
-use super::{hasher, Digest};
-use core::fmt;
+use super::{Digest, MerkleHasher, MerkleTreeVersion, Sha3Hasher};
+use core::{fmt, marker::PhantomData};
 use hex_fmt::HexFmt;
 
 #[derive(Clone, PartialEq)]
-pub struct Proof<T: AsRef<[u8]>> {
+pub struct Proof<T: AsRef<[u8]>, H: MerkleHasher = Sha3Hasher> {
     value: T,
     index: usize,
-    digests: Vec<Digest>,
-    root_hash: Digest,
+    digests: Vec<Digest<H>>,
+    root_hash: Digest<H>,
+    version: MerkleTreeVersion,
+    hasher: PhantomData<H>,
 }
 
-impl<T: AsRef<[u8]>> Proof<T> {
-    pub(crate) fn new(value: T, index: usize, digests: Vec<Digest>, root_hash: Digest) -> Self {
+impl<T: AsRef<[u8]>, H: MerkleHasher> Proof<T, H> {
+    pub(crate) fn new(
+        value: T,
+        index: usize,
+        digests: Vec<Digest<H>>,
+        root_hash: Digest<H>,
+        version: MerkleTreeVersion,
+    ) -> Self {
         Self {
             index,
             digests,
             value,
             root_hash,
+            version,
+            hasher: PhantomData,
         }
     }
 
     /// Returns `true` if the digests in this proof constitute a valid branch in a Merkle tree with
-    /// the root hash.
+    /// the root hash, hashed in the format of the default version.
     pub fn validate(&self, n: usize) -> bool {
-        let mut digest = hasher::hash(&self.value);
+        self.validate_with_version(n, MerkleTreeVersion::default())
+    }
+
+    /// Returns `true` if the proof is of the given version and its digests constitute a valid
+    /// branch in a Merkle tree with the root hash. The version is the receiver's, as a proof
+    /// claiming an older one would otherwise downgrade the hashing scheme.
+    pub fn validate_with_version(&self, n: usize, version: MerkleTreeVersion) -> bool {
+        if self.version != version {
+            return false;
+        }
+        let mut digest = self.version.hash_leaf::<H, _>(&self.value);
         let mut lvl_i = self.index;
         let mut lvl_n = n;
         let mut digest_itr = self.digests.iter();
@@ -31,8 +51,10 @@ impl<T: AsRef<[u8]>> Proof<T> {
             if lvl_i ^ 1 < lvl_n {
                 digest = match digest_itr.next() {
                     None => return false, // Not enough levels in the proof.
-                    Some(sibling) if lvl_i & 1 == 1 => hasher::hash_pair(&sibling, &digest),
-                    Some(sibling) => hasher::hash_pair(&digest, &sibling),
+                    Some(sibling) if lvl_i & 1 == 1 => {
+                        self.version.hash_node::<H>(sibling, &digest)
+                    }
+                    Some(sibling) => self.version.hash_node::<H>(&digest, sibling),
                 };
             }
             lvl_i /= 2; // Our index on the next level.
@@ -44,11 +66,15 @@ impl<T: AsRef<[u8]>> Proof<T> {
         digest == self.root_hash
     }
 
+    pub fn version(&self) -> MerkleTreeVersion {
+        self.version
+    }
+
     pub fn index(&self) -> usize {
         self.index
     }
 
-    pub fn root_hash(&self) -> &Digest {
+    pub fn root_hash(&self) -> &Digest<H> {
         &self.root_hash
     }
 
@@ -61,7 +87,7 @@ impl<T: AsRef<[u8]>> Proof<T> {
     }
 }
 
-impl<T: AsRef<[u8]>> fmt::Debug for Proof<T> {
+impl<T: AsRef<[u8]>, H: MerkleHasher> fmt::Debug for Proof<T, H> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
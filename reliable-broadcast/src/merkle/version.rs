@@ -0,0 +1,59 @@
+use super::{Digest, MerkleHasher};
+
+/// Prefixed to leaf values before hashing.
+const LEAF_PREFIX: &[u8] = &[0x00];
+
+/// Prefixed to the concatenated child digests of an interior node before hashing.
+const NODE_PREFIX: &[u8] = &[0x01];
+
+/// Format of the leaf and interior node hashes of a Merkle tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MerkleTreeVersion {
+    /// Leaves and interior nodes are hashed without any prefix, so a 64-byte leaf can't be told
+    /// apart from an interior node. Only kept to read trees built by older versions.
+    #[cfg(feature = "legacy-merkle-tree")]
+    Legacy,
+    /// Leaves and interior nodes are hashed with distinct prefixes.
+    #[default]
+    V1,
+}
+
+impl MerkleTreeVersion {
+    pub(crate) fn hash_leaf<H: MerkleHasher, T: AsRef<[u8]>>(&self, value: T) -> Digest<H> {
+        match self {
+            #[cfg(feature = "legacy-merkle-tree")]
+            Self::Legacy => H::hash(value),
+            Self::V1 => H::hash_pair(&LEAF_PREFIX, &value),
+        }
+    }
+
+    pub(crate) fn hash_node<H: MerkleHasher>(
+        &self,
+        left: &Digest<H>,
+        right: &Digest<H>,
+    ) -> Digest<H> {
+        match self {
+            #[cfg(feature = "legacy-merkle-tree")]
+            Self::Legacy => H::hash_pair(left, right),
+            Self::V1 => {
+                let bytes: Vec<u8> = NODE_PREFIX
+                    .iter()
+                    .chain(left.as_ref())
+                    .chain(right.as_ref())
+                    .cloned()
+                    .collect();
+                H::hash(&bytes)
+            }
+        }
+    }
+
+    /// Takes a chunk of one or two digests. In the former case, returns the digest itself, in the
+    /// latter, it returns the hash of the two digests.
+    pub(crate) fn hash_chunk<H: MerkleHasher>(&self, chunk: &[Digest<H>]) -> Digest<H> {
+        if chunk.len() == 1 {
+            chunk[0]
+        } else {
+            self.hash_node::<H>(&chunk[0], &chunk[1])
+        }
+    }
+}
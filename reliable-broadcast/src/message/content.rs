@@ -0,0 +1,236 @@
+use crate::merkle::{Digest, MerkleHasher, Proof, Sha3Hasher};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BroadcastMessageContent<H: MerkleHasher = Sha3Hasher> {
+    Value(ValueMessage<H>),
+    Echo(EchoMessage<H>),
+    Ready(ReadyMessage<H>),
+    PlainValue(PlainValueMessage),
+    PlainEcho(PlainEchoMessage),
+    EchoHash(EchoHashMessage<H>),
+    CanDecode(CanDecodeMessage<H>),
+    ShardRequest(ShardRequestMessage<H>),
+    ShardResponse(ShardResponseMessage<H>),
+}
+
+impl<H: MerkleHasher> BroadcastMessageContent<H> {
+    pub fn into_value(self) -> Option<ValueMessage<H>> {
+        match self {
+            Self::Value(inner) => Some(inner),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueMessage<H: MerkleHasher = Sha3Hasher>(Proof<Vec<u8>, H>);
+
+impl<H: MerkleHasher> ValueMessage<H> {
+    pub fn into_inner(self) -> Proof<Vec<u8>, H> {
+        self.0
+    }
+}
+
+impl<H: MerkleHasher> AsRef<Proof<Vec<u8>, H>> for ValueMessage<H> {
+    fn as_ref(&self) -> &Proof<Vec<u8>, H> {
+        &self.0
+    }
+}
+
+impl<H: MerkleHasher> From<Proof<Vec<u8>, H>> for ValueMessage<H> {
+    fn from(value: Proof<Vec<u8>, H>) -> Self {
+        Self(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EchoMessage<H: MerkleHasher = Sha3Hasher>(Proof<Vec<u8>, H>);
+
+impl<H: MerkleHasher> EchoMessage<H> {
+    pub fn into_inner(self) -> Proof<Vec<u8>, H> {
+        self.0
+    }
+}
+
+impl<H: MerkleHasher> AsRef<Proof<Vec<u8>, H>> for EchoMessage<H> {
+    fn as_ref(&self) -> &Proof<Vec<u8>, H> {
+        &self.0
+    }
+}
+
+impl<H: MerkleHasher> From<Proof<Vec<u8>, H>> for EchoMessage<H> {
+    fn from(value: Proof<Vec<u8>, H>) -> Self {
+        Self(value)
+    }
+}
+
+/// The full value of a plain mode broadcast, sent by the proposer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlainValueMessage(Vec<u8>);
+
+impl PlainValueMessage {
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl AsRef<Vec<u8>> for PlainValueMessage {
+    fn as_ref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for PlainValueMessage {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+/// The full value of a plain mode broadcast, echoed by every node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlainEchoMessage(Vec<u8>);
+
+impl PlainEchoMessage {
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl AsRef<Vec<u8>> for PlainEchoMessage {
+    fn as_ref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for PlainEchoMessage {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadyMessage<H: MerkleHasher = Sha3Hasher>(Digest<H>);
+
+impl<H: MerkleHasher> ReadyMessage<H> {
+    pub fn new(value: Digest<H>) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> Digest<H> {
+        self.0
+    }
+}
+
+impl<H: MerkleHasher> AsRef<Digest<H>> for ReadyMessage<H> {
+    fn as_ref(&self) -> &Digest<H> {
+        &self.0
+    }
+}
+
+impl<H: MerkleHasher<Digest = [u8; 32]>> From<[u8; 32]> for ReadyMessage<H> {
+    fn from(value: [u8; 32]) -> Self {
+        Self(value)
+    }
+}
+
+/// The root hash of an `Echo`, sent instead of the full shard to spare bandwidth.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EchoHashMessage<H: MerkleHasher = Sha3Hasher>(Digest<H>);
+
+impl<H: MerkleHasher> EchoHashMessage<H> {
+    pub fn new(value: Digest<H>) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> Digest<H> {
+        self.0
+    }
+}
+
+impl<H: MerkleHasher> AsRef<Digest<H>> for EchoHashMessage<H> {
+    fn as_ref(&self) -> &Digest<H> {
+        &self.0
+    }
+}
+
+impl<H: MerkleHasher<Digest = [u8; 32]>> From<[u8; 32]> for EchoHashMessage<H> {
+    fn from(value: [u8; 32]) -> Self {
+        Self(value)
+    }
+}
+
+/// Tells the receiver that the sender already holds enough shards to decode the value of the root
+/// hash, so that it is sent no more full `Echo` messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanDecodeMessage<H: MerkleHasher = Sha3Hasher>(Digest<H>);
+
+impl<H: MerkleHasher> CanDecodeMessage<H> {
+    pub fn new(value: Digest<H>) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> Digest<H> {
+        self.0
+    }
+}
+
+impl<H: MerkleHasher> AsRef<Digest<H>> for CanDecodeMessage<H> {
+    fn as_ref(&self) -> &Digest<H> {
+        &self.0
+    }
+}
+
+impl<H: MerkleHasher<Digest = [u8; 32]>> From<[u8; 32]> for CanDecodeMessage<H> {
+    fn from(value: [u8; 32]) -> Self {
+        Self(value)
+    }
+}
+
+/// Asks the receiver for its shard of the root hash, sent by a node which has received enough
+/// `Ready` messages to deliver but not enough shards to decode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardRequestMessage<H: MerkleHasher = Sha3Hasher>(Digest<H>);
+
+impl<H: MerkleHasher> ShardRequestMessage<H> {
+    pub fn new(value: Digest<H>) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> Digest<H> {
+        self.0
+    }
+}
+
+impl<H: MerkleHasher> AsRef<Digest<H>> for ShardRequestMessage<H> {
+    fn as_ref(&self) -> &Digest<H> {
+        &self.0
+    }
+}
+
+impl<H: MerkleHasher<Digest = [u8; 32]>> From<[u8; 32]> for ShardRequestMessage<H> {
+    fn from(value: [u8; 32]) -> Self {
+        Self(value)
+    }
+}
+
+/// The shard of the sender along with its Merkle proof, in answer to a `ShardRequest`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardResponseMessage<H: MerkleHasher = Sha3Hasher>(Proof<Vec<u8>, H>);
+
+impl<H: MerkleHasher> ShardResponseMessage<H> {
+    pub fn into_inner(self) -> Proof<Vec<u8>, H> {
+        self.0
+    }
+}
+
+impl<H: MerkleHasher> AsRef<Proof<Vec<u8>, H>> for ShardResponseMessage<H> {
+    fn as_ref(&self) -> &Proof<Vec<u8>, H> {
+        &self.0
+    }
+}
+
+impl<H: MerkleHasher> From<Proof<Vec<u8>, H>> for ShardResponseMessage<H> {
+    fn from(value: Proof<Vec<u8>, H>) -> Self {
+        Self(value)
+    }
+}
@@ -0,0 +1,35 @@
+/// How a proposer disseminates its value to the other nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BroadcastMode {
+    /// Reed-Solomon encodes the value and sends each node its own shard along with a Merkle proof.
+    #[default]
+    Erasure,
+    /// Plain Bracha broadcast: the full value is sent to and echoed by every node, and `Ready`
+    /// messages refer to its digest. Cheaper than `Erasure` for tiny values like votes or hashes.
+    Plain,
+    /// `Plain` for values of at most `max_plain_size` bytes, `Erasure` otherwise.
+    Auto { max_plain_size: usize },
+}
+
+impl BroadcastMode {
+    pub fn is_plain_for(&self, value_len: usize) -> bool {
+        match self {
+            Self::Erasure => false,
+            Self::Plain => true,
+            Self::Auto { max_plain_size } => value_len <= *max_plain_size,
+        }
+    }
+}
+
+/// How a node echoes the shard it received from the proposer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EchoMode {
+    /// Every node is sent the shard along with its Merkle proof.
+    #[default]
+    Full,
+    /// Only the N - 2f nodes following this one are sent the full `Echo`, the others an
+    /// `EchoHash` carrying the root hash. Nodes holding enough shards to decode announce it with
+    /// `CanDecode` and are sent no more shards. The shards withheld from the nodes which still
+    /// can't decode are sent once 2f + 1 `Ready` messages have been received.
+    Hash,
+}
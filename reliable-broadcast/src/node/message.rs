@@ -1,11 +1,13 @@
+use crate::merkle::{MerkleHasher, Sha3Hasher};
 use crate::message::BroadcastMessage;
 use crate::node::NodeId;
+use crate::session::SessionId;
 
 #[derive(Clone, PartialEq)]
-pub enum NodeMessage<ID: NodeId> {
+pub enum NodeMessage<ID: NodeId, SID: SessionId, H: MerkleHasher = Sha3Hasher> {
     BroadcastMessage {
         sender_id: ID,
-        message: BroadcastMessage,
+        message: BroadcastMessage<SID, H>,
     },
     Terminate,
 }
@@ -1,9 +1,11 @@
 use crate::{
     encode::Coder,
     merkle::Proof,
-    merkle::{Digest, MerkleTree},
+    merkle::{hasher, Digest, MerkleHasher, MerkleTree, MerkleTreeVersion},
     message::*,
+    mode::{BroadcastMode, EchoMode},
     node::{NodeId, NodeMessage},
+    session::SessionId,
     state::{FaultLog, FaultType, ReliableBroadcastState},
     validator::{ValidatorIndex, ValidatorSet},
     Error, Result,
@@ -12,28 +14,77 @@ use byteorder::{BigEndian, ByteOrder};
 use core::fmt;
 use std::collections::BTreeMap;
 
+/// Size of the length prefix of erasure coded values.
+const PAYLOAD_LEN_SIZE: usize = 4;
+
+/// The largest payload the length prefix of erasure coded values can describe.
+pub const MAX_PAYLOAD_SIZE: usize = u32::MAX as usize;
+
+type ProcedureState<RB> = ReliableBroadcastState<
+    <RB as ReliableBroadcast>::NodeId,
+    <RB as ReliableBroadcast>::ValidatorIndex,
+    <RB as ReliableBroadcast>::SessionId,
+    <RB as ReliableBroadcast>::Hasher,
+>;
+
 pub trait ReliableBroadcast: fmt::Debug {
     type NodeId: NodeId;
     type ValidatorIndex: ValidatorIndex;
+    type SessionId: SessionId;
+    type Hasher: MerkleHasher;
     fn my_id(&self) -> &Self::NodeId;
-    fn next_message(&self) -> NodeMessage<Self::NodeId>;
-    fn send_message(&self, target_id: Self::NodeId, message: BroadcastMessage);
+    fn next_message(&self) -> NodeMessage<Self::NodeId, Self::SessionId, Self::Hasher>;
+    fn send_message(
+        &self,
+        target_id: Self::NodeId,
+        message: BroadcastMessage<Self::SessionId, Self::Hasher>,
+    );
+    /// The mode `propose` disseminates the input with. Receivers handle messages of every mode.
+    fn broadcast_mode(&self) -> BroadcastMode {
+        BroadcastMode::default()
+    }
+    /// How the shard received from the proposer is echoed.
+    fn echo_mode(&self) -> EchoMode {
+        EchoMode::default()
+    }
+    /// The version of the Merkle trees `propose` builds, and the only one accepted in received
+    /// proofs. Every node must use the same.
+    fn merkle_tree_version(&self) -> MerkleTreeVersion {
+        MerkleTreeVersion::default()
+    }
+    /// The largest payload `propose` accepts and other proposers may disseminate, in bytes. Values
+    /// over `MAX_PAYLOAD_SIZE` can't be erasure coded whatever the limit.
+    fn max_payload_size(&self) -> usize {
+        MAX_PAYLOAD_SIZE
+    }
     fn handle_terminate_message(&self) {
         println!("{:?} has just detected terminate message.", self);
     }
-    fn handle_duplicated_value_message(&self, sender_id: &Self::NodeId, proof: &Proof<Vec<u8>>) {
+    fn handle_duplicated_value_message(
+        &self,
+        sender_id: &Self::NodeId,
+        proof: &Proof<Vec<u8>, Self::Hasher>,
+    ) {
         println!(
             "{:?} received Value({:?}) multiple times from {:?}.",
             self, proof, sender_id
         );
     }
-    fn handle_duplicated_echo_message(&self, sender_id: &Self::NodeId, proof: &Proof<Vec<u8>>) {
+    fn handle_duplicated_echo_message(
+        &self,
+        sender_id: &Self::NodeId,
+        proof: &Proof<Vec<u8>, Self::Hasher>,
+    ) {
         println!(
             "{:?} received Echo({:?}) multiple times from {:?}.",
             self, proof, sender_id
         );
     }
-    fn handle_duplicated_ready_message(&self, sender_id: &Self::NodeId, proof: &Digest) {
+    fn handle_duplicated_ready_message(
+        &self,
+        sender_id: &Self::NodeId,
+        proof: &Digest<Self::Hasher>,
+    ) {
         println!(
             "{:?} received Ready({:?}) multiple times from {:?}.",
             self, proof, sender_id
@@ -44,33 +95,73 @@ pub trait ReliableBroadcast: fmt::Debug {
         &self,
         input: Vec<u8>,
         validator_set: ValidatorSet<Self::NodeId, Self::ValidatorIndex>,
-    ) -> Result<ReliableBroadcastState<Self::NodeId, Self::ValidatorIndex>> {
+        session_id: Self::SessionId,
+    ) -> Result<ProcedureState<Self>> {
+        if input.len() > self.max_payload_size() {
+            return Err(Error::PayloadTooLarge {
+                size: input.len(),
+                max_size: self.max_payload_size(),
+            });
+        }
+        if self.broadcast_mode().is_plain_for(input.len()) {
+            return self.propose_plain(input, validator_set, session_id);
+        }
         let encoder = validator_set.as_encoder();
         let shards = encode_to_shards(&encoder, input)?;
-        let merkle_tree = MerkleTree::from(shards);
+        let merkle_tree =
+            MerkleTree::<_, Self::Hasher>::with_version(shards, self.merkle_tree_version());
         assert_eq!(validator_set.size(), merkle_tree.values().len());
         let mut initial_value_message = None;
         for (node_id, index) in validator_set.as_indices().clone() {
             let proof = merkle_tree.proof(index.into()).unwrap();
             let value_message = ValueMessage::from(proof);
             if node_id == *self.my_id() {
-                initial_value_message = Some(value_message);
+                initial_value_message = Some(BroadcastMessageContent::Value(value_message));
             } else {
-                self.send_message(node_id, BroadcastMessage::Value(value_message));
+                self.send_message(
+                    node_id,
+                    BroadcastMessage {
+                        session_id: session_id.clone(),
+                        content: BroadcastMessageContent::Value(value_message),
+                    },
+                );
+            }
+        }
+        self.execute(initial_value_message, validator_set, session_id)
+    }
+
+    /// Sends the full input to every node, as in plain Bracha broadcast.
+    fn propose_plain(
+        &self,
+        input: Vec<u8>,
+        validator_set: ValidatorSet<Self::NodeId, Self::ValidatorIndex>,
+        session_id: Self::SessionId,
+    ) -> Result<ProcedureState<Self>> {
+        for node_id in validator_set.as_indices().keys() {
+            if node_id != self.my_id() {
+                self.send_message(
+                    node_id.clone(),
+                    BroadcastMessage {
+                        session_id: session_id.clone(),
+                        content: BroadcastMessageContent::PlainValue(input.clone().into()),
+                    },
+                );
             }
         }
-        self.execute(initial_value_message, validator_set)
+        let initial_value_message = BroadcastMessageContent::PlainValue(input.into());
+        self.execute(Some(initial_value_message), validator_set, session_id)
     }
 
     /// execute reliable broadcast procedure
     fn execute(
         &self,
-        initial_value_message: Option<ValueMessage>,
+        initial_value_message: Option<BroadcastMessageContent<Self::Hasher>>,
         validator_set: ValidatorSet<Self::NodeId, Self::ValidatorIndex>,
-    ) -> Result<ReliableBroadcastState<Self::NodeId, Self::ValidatorIndex>> {
-        let mut state = ReliableBroadcastState::new(validator_set);
+        session_id: Self::SessionId,
+    ) -> Result<ProcedureState<Self>> {
+        let mut state = ReliableBroadcastState::new(validator_set, session_id);
         if let Some(initial_value_message) = initial_value_message {
-            self.handle_value(self.my_id(), initial_value_message, &mut state)?;
+            self.handle_content(self.my_id(), initial_value_message, &mut state)?;
         }
         loop {
             let node_message = self.next_message();
@@ -88,17 +179,15 @@ pub trait ReliableBroadcast: fmt::Debug {
                         });
                         continue;
                     }
-                    match message {
-                        BroadcastMessage::Value(message) => {
-                            self.handle_value(&sender_id, message, &mut state)?;
-                        }
-                        BroadcastMessage::Echo(message) => {
-                            self.handle_echo(&sender_id, message, &mut state)?;
-                        }
-                        BroadcastMessage::Ready(message) => {
-                            self.handle_ready(&sender_id, message, &mut state)?;
-                        }
+                    if message.session_id != *state.session_id() {
+                        state.push_fault_log(FaultLog {
+                            sender_id: sender_id.clone(),
+                            message,
+                            fault_type: FaultType::SessionMismatched,
+                        });
+                        continue;
                     }
+                    self.handle_content(&sender_id, message.content, &mut state)?;
                     if state.is_decided() {
                         break;
                     }
@@ -108,31 +197,97 @@ pub trait ReliableBroadcast: fmt::Debug {
         Ok(state)
     }
 
+    fn handle_content(
+        &self,
+        sender_id: &Self::NodeId,
+        content: BroadcastMessageContent<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        match content {
+            BroadcastMessageContent::Value(message) => self.handle_value(sender_id, message, state),
+            BroadcastMessageContent::Echo(message) => self.handle_echo(sender_id, message, state),
+            BroadcastMessageContent::Ready(message) => self.handle_ready(sender_id, message, state),
+            BroadcastMessageContent::PlainValue(message) => {
+                self.handle_plain_value(sender_id, message, state)
+            }
+            BroadcastMessageContent::PlainEcho(message) => {
+                self.handle_plain_echo(sender_id, message, state)
+            }
+            BroadcastMessageContent::EchoHash(message) => {
+                self.handle_echo_hash(sender_id, message, state)
+            }
+            BroadcastMessageContent::CanDecode(message) => {
+                self.handle_can_decode(sender_id, message, state)
+            }
+            BroadcastMessageContent::ShardRequest(message) => {
+                self.handle_shard_request(sender_id, message, state)
+            }
+            BroadcastMessageContent::ShardResponse(message) => {
+                self.handle_shard_response(sender_id, message, state)
+            }
+        }
+    }
+
     fn handle_value(
         &self,
         sender_id: &Self::NodeId,
-        message: ValueMessage,
-        state: &mut ReliableBroadcastState<Self::NodeId, Self::ValidatorIndex>,
+        message: ValueMessage<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
     ) -> Result<()> {
         let proof = message.as_ref();
         // validate proof first.
-        if !state.validate_proof(proof, self.my_id()) {
+        if !state.validate_proof(proof, self.my_id(), self.merkle_tree_version()) {
             state.push_fault_log(FaultLog {
                 sender_id: sender_id.clone(),
-                message: BroadcastMessage::Value(message),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::Value(message),
+                },
                 fault_type: FaultType::InvalidProof,
             });
             return Ok(());
         }
+        if is_shard_oversized(
+            state.encoder(),
+            proof.value().len(),
+            self.max_payload_size(),
+        ) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::Value(message),
+                },
+                fault_type: FaultType::PayloadTooLarge,
+            });
+            return Ok(());
+        }
 
         let root_hash = message.as_ref().root_hash();
+        if state.is_root_hash_evicted(root_hash) {
+            return Ok(());
+        }
+        if !state.try_open_root_hash(sender_id, root_hash) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::Value(message),
+                },
+                fault_type: FaultType::RootHashLimitExceeded,
+            });
+            return Ok(());
+        }
         let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
         if let Some(proposer) = root_hash_state.get_proposer() {
             // If we have already received value message
             return if proposer != sender_id {
                 state.push_fault_log(FaultLog {
                     sender_id: sender_id.clone(),
-                    message: BroadcastMessage::Value(message),
+                    message: BroadcastMessage {
+                        session_id: state.session_id().clone(),
+                        content: BroadcastMessageContent::Value(message),
+                    },
                     fault_type: FaultType::ReceivedValueFromNonProposer,
                 });
                 Ok(())
@@ -145,7 +300,10 @@ pub trait ReliableBroadcast: fmt::Debug {
                 } else {
                     state.push_fault_log(FaultLog {
                         sender_id: sender_id.clone(),
-                        message: BroadcastMessage::Value(message),
+                        message: BroadcastMessage {
+                            session_id: state.session_id().clone(),
+                            content: BroadcastMessageContent::Value(message),
+                        },
                         fault_type: FaultType::MultipleValueMessages,
                     });
                 };
@@ -154,29 +312,84 @@ pub trait ReliableBroadcast: fmt::Debug {
         }
 
         root_hash_state.set_proposer(sender_id.clone());
+        root_hash_state.set_echo_proof(proof.clone());
         root_hash_state.turn_echo_sent_on();
-        self.broadcast_echo_message(proof.clone(), state.validators().clone())?;
+        match self.echo_mode() {
+            EchoMode::Full => self.broadcast_echo_message(
+                proof.clone(),
+                state.session_id(),
+                state.validators().clone(),
+            )?,
+            EchoMode::Hash => self.send_echo_hash_messages(root_hash, state)?,
+        }
         Ok(())
     }
 
     fn handle_echo(
         &self,
         sender_id: &Self::NodeId,
-        message: EchoMessage,
-        state: &mut ReliableBroadcastState<Self::NodeId, Self::ValidatorIndex>,
+        message: EchoMessage<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
     ) -> Result<()> {
         let proof = message.as_ref();
         // validate proof first.
-        if !state.validate_proof(proof, sender_id) {
+        if !state.validate_proof(proof, sender_id, self.merkle_tree_version()) {
             state.push_fault_log(FaultLog {
                 sender_id: sender_id.clone(),
-                message: BroadcastMessage::Echo(message),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::Echo(message),
+                },
                 fault_type: FaultType::InvalidProof,
             });
             return Ok(());
         }
+        if is_shard_oversized(
+            state.encoder(),
+            proof.value().len(),
+            self.max_payload_size(),
+        ) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::Echo(message),
+                },
+                fault_type: FaultType::PayloadTooLarge,
+            });
+            return Ok(());
+        }
 
         let root_hash = proof.root_hash();
+        // only the first echo of each sender counts, whatever root hash it is for
+        if let Some(first_root_hash) = state.get_echo_root_hash(sender_id) {
+            if first_root_hash != root_hash {
+                state.push_fault_log(FaultLog {
+                    sender_id: sender_id.clone(),
+                    message: BroadcastMessage {
+                        session_id: state.session_id().clone(),
+                        content: BroadcastMessageContent::Echo(message),
+                    },
+                    fault_type: FaultType::MultipleEchoMessages,
+                });
+                return Ok(());
+            }
+        }
+        state.insert_echo_root_hash(sender_id.clone(), *root_hash);
+        if state.is_root_hash_evicted(root_hash) {
+            return Ok(());
+        }
+        if !state.try_open_root_hash(sender_id, root_hash) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::Echo(message),
+                },
+                fault_type: FaultType::RootHashLimitExceeded,
+            });
+            return Ok(());
+        }
         let min_guarantee_size = state.validator_set().min_guarantee_size();
         let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
 
@@ -187,7 +400,10 @@ pub trait ReliableBroadcast: fmt::Debug {
             } else {
                 state.push_fault_log(FaultLog {
                     sender_id: sender_id.clone(),
-                    message: BroadcastMessage::Echo(message),
+                    message: BroadcastMessage {
+                        session_id: state.session_id().clone(),
+                        content: BroadcastMessageContent::Echo(message),
+                    },
                     fault_type: FaultType::MultipleEchoMessages,
                 });
             };
@@ -199,21 +415,292 @@ pub trait ReliableBroadcast: fmt::Debug {
         {
             // it's high time to broadcast ready messages
             root_hash_state.turn_ready_sent_on();
-            self.broadcast_ready_message(*root_hash, state.validators().clone())?;
+            self.broadcast_ready_message(
+                *root_hash,
+                state.session_id(),
+                state.validators().clone(),
+            )?;
         }
-        if state.can_compute_output(root_hash) {
+        let data_shard_count = state.encoder().data_shard_count();
+        let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
+        if self.echo_mode() == EchoMode::Hash
+            && !root_hash_state.is_can_decode_sent()
+            && root_hash_state.count_received_shards() >= data_shard_count
+        {
+            // we need no more shards, so tell the others to stop sending them
+            root_hash_state.turn_can_decode_sent_on();
+            self.broadcast_can_decode_message(
+                *root_hash,
+                state.session_id(),
+                state.validators().clone(),
+            )?;
+        }
+        state.evict_unreachable_root_hashes();
+        if !state.is_root_hash_evicted(root_hash) && state.can_compute_output(root_hash) {
             self.compute_output(root_hash, state)?;
         }
         Ok(())
     }
 
+    fn handle_echo_hash(
+        &self,
+        sender_id: &Self::NodeId,
+        message: EchoHashMessage<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        let root_hash = message.as_ref();
+        // only the first echo of each sender counts, whatever root hash it is for
+        if let Some(first_root_hash) = state.get_echo_root_hash(sender_id) {
+            if first_root_hash != root_hash {
+                state.push_fault_log(FaultLog {
+                    sender_id: sender_id.clone(),
+                    message: BroadcastMessage {
+                        session_id: state.session_id().clone(),
+                        content: BroadcastMessageContent::EchoHash(message),
+                    },
+                    fault_type: FaultType::MultipleEchoMessages,
+                });
+                return Ok(());
+            }
+        }
+        state.insert_echo_root_hash(sender_id.clone(), *root_hash);
+        if state.is_root_hash_evicted(root_hash) {
+            return Ok(());
+        }
+        if !state.try_open_root_hash(sender_id, root_hash) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::EchoHash(message),
+                },
+                fault_type: FaultType::RootHashLimitExceeded,
+            });
+            return Ok(());
+        }
+        let min_guarantee_size = state.validator_set().min_guarantee_size();
+        let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
+
+        if !root_hash_state.insert_received_echo_hash_message(sender_id.clone()) {
+            // if duplicated, just ignore it
+            return Ok(());
+        }
+        if !root_hash_state.is_ready_sent()
+            && root_hash_state.count_received_echo_messages() >= min_guarantee_size
+        {
+            // it's high time to broadcast ready messages
+            root_hash_state.turn_ready_sent_on();
+            self.broadcast_ready_message(
+                *root_hash,
+                state.session_id(),
+                state.validators().clone(),
+            )?;
+        }
+        state.evict_unreachable_root_hashes();
+        if !state.is_root_hash_evicted(root_hash) && state.can_compute_output(root_hash) {
+            self.compute_output(root_hash, state)?;
+        }
+        Ok(())
+    }
+
+    fn handle_can_decode(
+        &self,
+        sender_id: &Self::NodeId,
+        message: CanDecodeMessage<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        let root_hash = message.as_ref();
+        if state.is_root_hash_evicted(root_hash) {
+            return Ok(());
+        }
+        if !state.try_open_root_hash(sender_id, root_hash) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::CanDecode(message),
+                },
+                fault_type: FaultType::RootHashLimitExceeded,
+            });
+            return Ok(());
+        }
+        // duplicates are harmless
+        state
+            .get_or_init_mut_root_hash_state(*root_hash)
+            .insert_received_can_decode_message(sender_id.clone());
+        Ok(())
+    }
+
+    fn handle_plain_value(
+        &self,
+        sender_id: &Self::NodeId,
+        message: PlainValueMessage,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        if message.as_ref().len() > self.max_payload_size() {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::PlainValue(message),
+                },
+                fault_type: FaultType::PayloadTooLarge,
+            });
+            return Ok(());
+        }
+        let root_hash = hasher::hash_plain_value::<Self::Hasher, _>(message.as_ref());
+        if state.is_root_hash_evicted(&root_hash) {
+            return Ok(());
+        }
+        if !state.try_open_root_hash(sender_id, &root_hash) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::PlainValue(message),
+                },
+                fault_type: FaultType::RootHashLimitExceeded,
+            });
+            return Ok(());
+        }
+        let root_hash_state = state.get_or_init_mut_root_hash_state(root_hash);
+        if let Some(proposer) = root_hash_state.get_proposer() {
+            if proposer != sender_id {
+                state.push_fault_log(FaultLog {
+                    sender_id: sender_id.clone(),
+                    message: BroadcastMessage {
+                        session_id: state.session_id().clone(),
+                        content: BroadcastMessageContent::PlainValue(message),
+                    },
+                    fault_type: FaultType::ReceivedValueFromNonProposer,
+                });
+            }
+            // the same value from the proposer again is harmless
+            return Ok(());
+        }
+
+        root_hash_state.set_proposer(sender_id.clone());
+        root_hash_state.set_value(message.as_ref().clone());
+        root_hash_state.turn_echo_sent_on();
+        self.broadcast_plain_echo_message(
+            message.into_inner(),
+            state.session_id(),
+            state.validators().clone(),
+        )?;
+        Ok(())
+    }
+
+    fn handle_plain_echo(
+        &self,
+        sender_id: &Self::NodeId,
+        message: PlainEchoMessage,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        if message.as_ref().len() > self.max_payload_size() {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::PlainEcho(message),
+                },
+                fault_type: FaultType::PayloadTooLarge,
+            });
+            return Ok(());
+        }
+        let root_hash = hasher::hash_plain_value::<Self::Hasher, _>(message.as_ref());
+        // only the first echo of each sender counts, whatever root hash it is for
+        if let Some(first_root_hash) = state.get_echo_root_hash(sender_id) {
+            if *first_root_hash != root_hash {
+                state.push_fault_log(FaultLog {
+                    sender_id: sender_id.clone(),
+                    message: BroadcastMessage {
+                        session_id: state.session_id().clone(),
+                        content: BroadcastMessageContent::PlainEcho(message),
+                    },
+                    fault_type: FaultType::MultipleEchoMessages,
+                });
+                return Ok(());
+            }
+        }
+        state.insert_echo_root_hash(sender_id.clone(), root_hash);
+        if state.is_root_hash_evicted(&root_hash) {
+            return Ok(());
+        }
+        if !state.try_open_root_hash(sender_id, &root_hash) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::PlainEcho(message),
+                },
+                fault_type: FaultType::RootHashLimitExceeded,
+            });
+            return Ok(());
+        }
+        let min_guarantee_size = state.validator_set().min_guarantee_size();
+        let root_hash_state = state.get_or_init_mut_root_hash_state(root_hash);
+
+        if !root_hash_state.insert_received_plain_echo_message(sender_id.clone()) {
+            // if duplicated, just ignore it
+            return Ok(());
+        }
+        if root_hash_state.get_value().is_none() {
+            root_hash_state.set_value(message.into_inner());
+        }
+        if !root_hash_state.is_ready_sent()
+            && root_hash_state.count_received_echo_messages() >= min_guarantee_size
+        {
+            // it's high time to broadcast ready messages
+            root_hash_state.turn_ready_sent_on();
+            self.broadcast_ready_message(
+                root_hash,
+                state.session_id(),
+                state.validators().clone(),
+            )?;
+        }
+        state.evict_unreachable_root_hashes();
+        if !state.is_root_hash_evicted(&root_hash) && state.can_compute_output(&root_hash) {
+            self.compute_output(&root_hash, state)?;
+        }
+        Ok(())
+    }
+
     fn handle_ready(
         &self,
         sender_id: &Self::NodeId,
-        message: ReadyMessage,
-        state: &mut ReliableBroadcastState<Self::NodeId, Self::ValidatorIndex>,
+        message: ReadyMessage<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
     ) -> Result<()> {
         let root_hash = message.as_ref();
+        // only the first ready of each sender counts, whatever root hash it is for
+        if let Some(first_root_hash) = state.get_ready_root_hash(sender_id) {
+            if first_root_hash != root_hash {
+                state.push_fault_log(FaultLog {
+                    sender_id: sender_id.clone(),
+                    message: BroadcastMessage {
+                        session_id: state.session_id().clone(),
+                        content: BroadcastMessageContent::Ready(message),
+                    },
+                    fault_type: FaultType::MultipleReadyMessages,
+                });
+                return Ok(());
+            }
+        }
+        state.insert_ready_root_hash(sender_id.clone(), *root_hash);
+        if state.is_root_hash_evicted(root_hash) {
+            return Ok(());
+        }
+        if !state.try_open_root_hash(sender_id, root_hash) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::Ready(message),
+                },
+                fault_type: FaultType::RootHashLimitExceeded,
+            });
+            return Ok(());
+        }
         let max_durable_faulty_size = state.validator_set().max_durable_faulty_size();
         let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
 
@@ -222,12 +709,120 @@ pub trait ReliableBroadcast: fmt::Debug {
             return Ok(());
         }
 
+        let is_output_threshold_reached =
+            root_hash_state.count_received_ready_messages() == 2 * max_durable_faulty_size + 1;
         if !root_hash_state.is_ready_sent()
             && root_hash_state.count_received_ready_messages() >= max_durable_faulty_size + 1
         {
             root_hash_state.turn_ready_sent_on();
             // to amplify ready messages, broadcast ready message to all
-            self.broadcast_ready_message(*root_hash, state.validators().clone())?;
+            self.broadcast_ready_message(
+                *root_hash,
+                state.session_id(),
+                state.validators().clone(),
+            )?;
+        }
+        if is_output_threshold_reached {
+            // every honest node is about to decode, so send the shards withheld so far
+            self.send_remaining_echo_messages(root_hash, state)?;
+            self.request_missing_shards(root_hash, state)?;
+        }
+        state.evict_unreachable_root_hashes();
+        if !state.is_root_hash_evicted(root_hash) && state.can_compute_output(root_hash) {
+            self.compute_output(root_hash, state)?;
+        }
+        Ok(())
+    }
+
+    fn handle_shard_request(
+        &self,
+        sender_id: &Self::NodeId,
+        message: ShardRequestMessage<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        let root_hash = message.as_ref();
+        // without a root hash state, we have no shard to answer with
+        if !state.has_root_hash_state(root_hash) {
+            return Ok(());
+        }
+        let session_id = state.session_id().clone();
+        let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
+        let proof = match root_hash_state.get_echo_proof() {
+            Some(proof) => proof.clone(),
+            None => return Ok(()),
+        };
+        if !root_hash_state.insert_answered_shard_request(sender_id.clone()) {
+            // each node is answered once
+            return Ok(());
+        }
+        self.send_message(
+            sender_id.clone(),
+            BroadcastMessage {
+                session_id,
+                content: BroadcastMessageContent::ShardResponse(proof.into()),
+            },
+        );
+        Ok(())
+    }
+
+    fn handle_shard_response(
+        &self,
+        sender_id: &Self::NodeId,
+        message: ShardResponseMessage<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        let proof = message.as_ref();
+        // validate proof first.
+        if !state.validate_proof(proof, sender_id, self.merkle_tree_version()) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::ShardResponse(message),
+                },
+                fault_type: FaultType::InvalidProof,
+            });
+            return Ok(());
+        }
+        if is_shard_oversized(
+            state.encoder(),
+            proof.value().len(),
+            self.max_payload_size(),
+        ) {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::ShardResponse(message),
+                },
+                fault_type: FaultType::PayloadTooLarge,
+            });
+            return Ok(());
+        }
+
+        let root_hash = proof.root_hash();
+        if state.is_root_hash_evicted(root_hash) {
+            return Ok(());
+        }
+        if !state.has_root_hash_state(root_hash)
+            || !state.get_root_hash_state(root_hash).is_shard_request_sent()
+        {
+            state.push_fault_log(FaultLog {
+                sender_id: sender_id.clone(),
+                message: BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::ShardResponse(message),
+                },
+                fault_type: FaultType::UnrequestedShardResponse,
+            });
+            return Ok(());
+        }
+        let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
+        if root_hash_state
+            .get_received_echo_message(sender_id)
+            .is_none()
+        {
+            root_hash_state.insert_received_echo_message(sender_id.clone(), proof.to_owned());
         }
         if state.can_compute_output(root_hash) {
             self.compute_output(root_hash, state)?;
@@ -235,13 +830,44 @@ pub trait ReliableBroadcast: fmt::Debug {
         Ok(())
     }
 
+    /// Answers the `ShardRequest` messages of nodes which are late to deliver, once this node has
+    /// delivered, until `Terminate` is received. Other messages are ignored.
+    fn serve_shard_requests(&self, state: &mut ProcedureState<Self>) -> Result<()> {
+        loop {
+            let node_message = self.next_message();
+            match node_message {
+                NodeMessage::Terminate => {
+                    self.handle_terminate_message();
+                    break;
+                }
+                NodeMessage::BroadcastMessage { sender_id, message } => {
+                    if !state.validator_set().contains(&sender_id)
+                        || message.session_id != *state.session_id()
+                    {
+                        continue;
+                    }
+                    if let BroadcastMessageContent::ShardRequest(message) = message.content {
+                        self.handle_shard_request(&sender_id, message, state)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn compute_output(
         &self,
-        root_hash: &Digest,
-        state: &mut ReliableBroadcastState<Self::NodeId, Self::ValidatorIndex>,
+        root_hash: &Digest<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
     ) -> Result<()> {
         let validators = state.validators();
         let root_hash_state = state.get_root_hash_state(root_hash);
+        if let Some(value) = root_hash_state.get_value() {
+            // plain mode
+            let output = value.clone();
+            state.set_output(output);
+            return Ok(());
+        }
         let mut shards: Vec<Option<Box<[u8]>>> = validators
             .keys()
             .map(|id| {
@@ -256,20 +882,192 @@ pub trait ReliableBroadcast: fmt::Debug {
                     })
             })
             .collect();
+        let proposer_and_proof = root_hash_state
+            .get_proposer()
+            .cloned()
+            .zip(root_hash_state.get_echo_proof().cloned());
         let decoder = state.encoder();
-        let output = decode_from_shards(&decoder, &mut shards, Some(root_hash))?;
-        state.set_output(output);
+        let result = decode_from_shards::<Self::Hasher>(
+            decoder,
+            &mut shards,
+            Some(root_hash),
+            self.merkle_tree_version(),
+            self.max_payload_size(),
+        );
+        match result {
+            Ok(output) => state.set_output(output),
+            Err(err) => {
+                // The proposer is faulty: its shards don't decode to a valid payload, so no honest
+                // node can output for this root hash.
+                let fault_type = match err {
+                    Error::PayloadTooLarge { .. } => FaultType::PayloadTooLarge,
+                    _ => FaultType::InvalidEncoding,
+                };
+                // the proposer is only known once its `Value` message has been received.
+                if let Some((proposer, proof)) = proposer_and_proof {
+                    state.push_fault_log(FaultLog {
+                        sender_id: proposer,
+                        message: BroadcastMessage {
+                            session_id: state.session_id().clone(),
+                            content: BroadcastMessageContent::Value(proof.into()),
+                        },
+                        fault_type,
+                    });
+                }
+                state.evict_root_hash(root_hash);
+            }
+        }
         Ok(())
     }
 
     fn broadcast_echo_message(
         &self,
-        value: Proof<Vec<u8>>,
+        value: Proof<Vec<u8>, Self::Hasher>,
+        session_id: &Self::SessionId,
+        validators: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
+    ) -> Result<()> {
+        for (node_id, _index) in validators {
+            if node_id != *self.my_id() {
+                let message = BroadcastMessage {
+                    session_id: session_id.clone(),
+                    content: BroadcastMessageContent::Echo(value.clone().into()),
+                };
+                self.send_message(node_id, message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the full `Echo` to the N - 2f nodes following this one, and an `EchoHash` to the
+    /// others. Every node is sent the full `Echo` if 2f + 1 `Ready` messages have already been
+    /// received, except for those which have announced they can decode.
+    fn send_echo_hash_messages(
+        &self,
+        root_hash: &Digest<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        let node_ids: Vec<Self::NodeId> = state.validators().keys().cloned().collect();
+        let my_position = node_ids
+            .iter()
+            .position(|node_id| node_id == self.my_id())
+            .unwrap_or_default();
+        let full_echo_count = state.encoder().data_shard_count();
+        let max_durable_faulty_size = state.validator_set().max_durable_faulty_size();
+        let session_id = state.session_id().clone();
+        let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
+        let proof = root_hash_state
+            .get_echo_proof()
+            .expect("echo proof must be set...")
+            .clone();
+        let is_output_threshold_reached =
+            root_hash_state.count_received_ready_messages() > 2 * max_durable_faulty_size;
+        for offset in 1..node_ids.len() {
+            let node_id = &node_ids[(my_position + offset) % node_ids.len()];
+            let content = if (offset <= full_echo_count || is_output_threshold_reached)
+                && !root_hash_state.is_can_decode_received(node_id)
+            {
+                BroadcastMessageContent::Echo(proof.clone().into())
+            } else {
+                root_hash_state.insert_echo_hash_target(node_id.clone());
+                BroadcastMessageContent::EchoHash(EchoHashMessage::new(*root_hash))
+            };
+            self.send_message(
+                node_id.clone(),
+                BroadcastMessage {
+                    session_id: session_id.clone(),
+                    content,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Sends the full `Echo` to the nodes which have only been sent an `EchoHash` and still can't
+    /// decode.
+    fn send_remaining_echo_messages(
+        &self,
+        root_hash: &Digest<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        let session_id = state.session_id().clone();
+        let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
+        let proof = match root_hash_state.get_echo_proof() {
+            Some(proof) => proof.clone(),
+            // our own `Echo` hasn't been sent yet
+            None => return Ok(()),
+        };
+        for node_id in root_hash_state.take_echo_hash_targets() {
+            self.send_message(
+                node_id,
+                BroadcastMessage {
+                    session_id: session_id.clone(),
+                    content: BroadcastMessageContent::Echo(proof.clone().into()),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Asks every node for its shard if there aren't enough of them to decode, e.g. because the
+    /// `Value` message or the `Echo` messages have been missed.
+    fn request_missing_shards(
+        &self,
+        root_hash: &Digest<Self::Hasher>,
+        state: &mut ProcedureState<Self>,
+    ) -> Result<()> {
+        let data_shard_count = state.encoder().data_shard_count();
+        let root_hash_state = state.get_or_init_mut_root_hash_state(*root_hash);
+        if root_hash_state.get_value().is_some()
+            || root_hash_state.is_shard_request_sent()
+            || root_hash_state.count_received_shards() >= data_shard_count
+        {
+            return Ok(());
+        }
+        root_hash_state.turn_shard_request_sent_on();
+        for node_id in state.validators().keys() {
+            if node_id != self.my_id() {
+                let message = BroadcastMessage {
+                    session_id: state.session_id().clone(),
+                    content: BroadcastMessageContent::ShardRequest(ShardRequestMessage::new(
+                        *root_hash,
+                    )),
+                };
+                self.send_message(node_id.clone(), message);
+            }
+        }
+        Ok(())
+    }
+
+    fn broadcast_can_decode_message(
+        &self,
+        value: Digest<Self::Hasher>,
+        session_id: &Self::SessionId,
+        validators: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
+    ) -> Result<()> {
+        for (node_id, _) in validators {
+            if node_id != *self.my_id() {
+                let message = BroadcastMessage {
+                    session_id: session_id.clone(),
+                    content: BroadcastMessageContent::CanDecode(CanDecodeMessage::new(value)),
+                };
+                self.send_message(node_id, message);
+            }
+        }
+        Ok(())
+    }
+
+    fn broadcast_plain_echo_message(
+        &self,
+        value: Vec<u8>,
+        session_id: &Self::SessionId,
         validators: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
     ) -> Result<()> {
         for (node_id, _index) in validators {
             if node_id != *self.my_id() {
-                let message = BroadcastMessage::Echo(value.clone().into());
+                let message = BroadcastMessage {
+                    session_id: session_id.clone(),
+                    content: BroadcastMessageContent::PlainEcho(value.clone().into()),
+                };
                 self.send_message(node_id, message);
             }
         }
@@ -278,12 +1076,16 @@ pub trait ReliableBroadcast: fmt::Debug {
 
     fn broadcast_ready_message(
         &self,
-        value: Digest,
+        value: Digest<Self::Hasher>,
+        session_id: &Self::SessionId,
         validators: BTreeMap<Self::NodeId, Self::ValidatorIndex>,
     ) -> Result<()> {
         for (node_id, _) in validators {
             if node_id != *self.my_id() {
-                let message = BroadcastMessage::Ready(value.into());
+                let message = BroadcastMessage {
+                    session_id: session_id.clone(),
+                    content: BroadcastMessageContent::Ready(ReadyMessage::new(value)),
+                };
                 self.send_message(node_id, message);
             }
         }
@@ -297,13 +1099,15 @@ fn encode_to_shards(encoder: &Coder, mut value: Vec<u8>) -> Result<Vec<Vec<u8>>>
     let parity_shard_count = encoder.parity_shard_count();
 
     // insert the length of `value` so it can be decoded without the padding.
-    let payload_len = value.len() as u32;
-    value.splice(0..0, 0..4); // Insert 4 bytes at the beginning.
-    BigEndian::write_u32(&mut value[..4], payload_len); // Write the size.
+    let payload_len = u32::try_from(value.len()).map_err(|_| Error::PayloadTooLarge {
+        size: value.len(),
+        max_size: MAX_PAYLOAD_SIZE,
+    })?;
+    value.splice(0..0, 0..PAYLOAD_LEN_SIZE as u8); // Insert 4 bytes at the beginning.
+    BigEndian::write_u32(&mut value[..PAYLOAD_LEN_SIZE], payload_len); // Write the size.
 
-    // Size of a Merkle tree leaf value: the value size divided by the number of data shards,
-    // and rounded up, so that the full value always fits in the data shards. Always at least 1.
-    let shard_len = (value.len() + data_shard_count - 1) / data_shard_count;
+    // Size of a Merkle tree leaf value. Always at least 1.
+    let shard_len = encoder.shard_len(value.len());
     // Pad the last data shard with zeros. Fill the parity shards with zeros.
     value.resize(shard_len * (data_shard_count + parity_shard_count), 0);
 
@@ -316,10 +1120,18 @@ fn encode_to_shards(encoder: &Coder, mut value: Vec<u8>) -> Result<Vec<Vec<u8>>>
     Ok(shards.into_iter().map(|shard| shard.to_vec()).collect())
 }
 
-fn decode_from_shards(
+/// Returns whether a shard is longer than those of the largest payload allowed, in which case the
+/// proposer is faulty.
+fn is_shard_oversized(encoder: &Coder, shard_len: usize, max_payload_size: usize) -> bool {
+    shard_len > encoder.shard_len(max_payload_size.saturating_add(PAYLOAD_LEN_SIZE))
+}
+
+fn decode_from_shards<H: MerkleHasher>(
     decoder: &Coder,
     shards: &mut [Option<Box<[u8]>>],
-    root_hash: Option<&Digest>,
+    root_hash: Option<&Digest<H>>,
+    version: MerkleTreeVersion,
+    max_payload_size: usize,
 ) -> Result<Vec<u8>> {
     // Try to interpolate the Merkle tree using the Reed-Solomon erasure coding scheme.
     decoder.reconstruct(shards)?;
@@ -330,7 +1142,7 @@ fn decode_from_shards(
         .filter_map(|shard| shard.as_ref().map(|v| v.to_vec()))
         .collect();
 
-    let merkle_tree = MerkleTree::from(shards);
+    let merkle_tree = MerkleTree::<_, H>::with_version(shards, version);
     if let Some(root_hash_to_be_checked) = root_hash {
         if merkle_tree.root_hash() != root_hash_to_be_checked {
             return Err(Error::IllegalMerkleTreeRootHash);
@@ -344,6 +1156,13 @@ fn decode_from_shards(
         }
         _ => Err(Error::MerkleTreeMissingPayloadLength), // The proposer is faulty: no payload size.
     }?;
+    if payload_len > max_payload_size {
+        // The proposer is faulty: its shards are short enough, but not the claimed length.
+        return Err(Error::PayloadTooLarge {
+            size: payload_len,
+            max_size: max_payload_size,
+        });
+    }
     let payload: Vec<u8> = bytes.take(payload_len).collect();
     Ok(payload)
 }
@@ -351,6 +1170,7 @@ fn decode_from_shards(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::merkle::Sha3Hasher;
 
     #[test]
     fn test_encode_and_decode() {
@@ -371,7 +1191,45 @@ mod tests {
         shards[0] = None;
         shards[4] = None;
 
-        let decoded = decode_from_shards(&coder, shards.as_mut_slice(), None).unwrap();
+        let decoded = decode_from_shards::<Sha3Hasher>(
+            &coder,
+            shards.as_mut_slice(),
+            None,
+            MerkleTreeVersion::default(),
+            MAX_PAYLOAD_SIZE,
+        )
+        .unwrap();
         assert_eq!(input, decoded.as_slice());
     }
+
+    #[test]
+    fn test_decode_rejects_oversized_payload() {
+        let coder = Coder::new(3, 2).unwrap();
+        let shards = encode_to_shards(&coder, b"test".to_vec()).unwrap();
+        let mut shards: Vec<Option<Box<[u8]>>> = shards
+            .into_iter()
+            .map(|shard| Some(shard.into_boxed_slice()))
+            .collect();
+        // the shards of a 4-byte payload are no longer than those of a 3-byte one.
+        assert!(!is_shard_oversized(
+            &coder,
+            shards[0].as_ref().unwrap().len(),
+            3
+        ));
+
+        let result = decode_from_shards::<Sha3Hasher>(
+            &coder,
+            shards.as_mut_slice(),
+            None,
+            MerkleTreeVersion::default(),
+            3,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::PayloadTooLarge {
+                size: 4,
+                max_size: 3
+            })
+        ));
+    }
 }
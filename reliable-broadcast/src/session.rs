@@ -0,0 +1,2 @@
+mod id;
+pub use id::SessionId;
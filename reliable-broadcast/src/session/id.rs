@@ -0,0 +1,5 @@
+use core::fmt;
+
+/// Identifies a reliable broadcast instance, e.g. an (epoch, proposer) pair of an upper protocol.
+pub trait SessionId: Clone + Eq + fmt::Display + fmt::Debug + Send + Sync {}
+impl<ID> SessionId for ID where ID: Clone + Eq + fmt::Display + fmt::Debug + Send + Sync {}
@@ -5,28 +5,62 @@ pub use root_hash::RootHashState;
 
 use crate::{
     encode::Coder,
-    merkle::{Digest, Proof},
+    merkle::{Digest, MerkleHasher, MerkleTreeVersion, Proof, Sha3Hasher},
     node::NodeId,
+    session::SessionId,
     validator::{ValidatorIndex, ValidatorSet},
 };
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-pub struct ReliableBroadcastState<ID: NodeId, IDX: ValidatorIndex> {
+/// Maximum number of distinct root hashes a single sender may open. An honest sender refers to at
+/// most two of them: the one it echoes and the one it is ready for.
+pub const MAX_ROOT_HASHES_PER_SENDER: usize = 2;
+
+type OutputAndFaultLogs<ID, SID, H> = (Option<Vec<u8>>, Vec<FaultLog<ID, SID, H>>);
+
+pub struct ReliableBroadcastState<
+    ID: NodeId,
+    IDX: ValidatorIndex,
+    SID: SessionId,
+    H: MerkleHasher = Sha3Hasher,
+> {
     /// validators
     validator_set: ValidatorSet<ID, IDX>,
 
-    root_hash_states: BTreeMap<Digest, RootHashState<ID>>,
+    /// Session identifier, to prevent messages of other instances from being counted.
+    session_id: SID,
+
+    root_hash_states: BTreeMap<Digest<H>, RootHashState<ID, H>>,
+
+    /// Root hashes whose state has been created by each sender's message.
+    opened_root_hashes: BTreeMap<ID, BTreeSet<Digest<H>>>,
+
+    /// Root hashes dropped because no honest quorum can reach them anymore.
+    evicted_root_hashes: BTreeSet<Digest<H>>,
+
+    /// The root hash of the first `Echo` message received from each sender.
+    echo_root_hashes: BTreeMap<ID, Digest<H>>,
 
-    fault_logs: Vec<FaultLog<ID>>,
+    /// The root hash of the first `Ready` message received from each sender.
+    ready_root_hashes: BTreeMap<ID, Digest<H>>,
+
+    fault_logs: Vec<FaultLog<ID, SID, H>>,
 
     output: Option<Vec<u8>>,
 }
 
-impl<ID: NodeId, IDX: ValidatorIndex> ReliableBroadcastState<ID, IDX> {
-    pub fn new(validator_set: ValidatorSet<ID, IDX>) -> Self {
+impl<ID: NodeId, IDX: ValidatorIndex, SID: SessionId, H: MerkleHasher>
+    ReliableBroadcastState<ID, IDX, SID, H>
+{
+    pub fn new(validator_set: ValidatorSet<ID, IDX>, session_id: SID) -> Self {
         Self {
             validator_set,
+            session_id,
             root_hash_states: BTreeMap::new(),
+            opened_root_hashes: BTreeMap::new(),
+            evicted_root_hashes: BTreeSet::new(),
+            echo_root_hashes: BTreeMap::new(),
+            ready_root_hashes: BTreeMap::new(),
             fault_logs: Vec::new(),
             output: None,
         }
@@ -40,48 +74,143 @@ impl<ID: NodeId, IDX: ValidatorIndex> ReliableBroadcastState<ID, IDX> {
         &self.validator_set
     }
 
+    pub fn session_id(&self) -> &SID {
+        &self.session_id
+    }
+
     pub fn validators(&self) -> &BTreeMap<ID, IDX> {
         self.validator_set.as_indices()
     }
 
-    pub fn get_root_hash_state(&self, root_hash: &Digest) -> &RootHashState<ID> {
+    pub fn get_root_hash_state(&self, root_hash: &Digest<H>) -> &RootHashState<ID, H> {
         self.root_hash_states
             .get(root_hash)
             .expect("root hash state must be initialized...")
     }
 
-    pub fn get_or_init_mut_root_hash_state(&mut self, root_hash: Digest) -> &mut RootHashState<ID> {
+    pub fn get_or_init_mut_root_hash_state(
+        &mut self,
+        root_hash: Digest<H>,
+    ) -> &mut RootHashState<ID, H> {
         self.root_hash_states
             .entry(root_hash)
             .or_insert_with(RootHashState::default)
     }
 
-    pub fn count_echo_messages(&self, digest: &Digest) -> usize {
+    pub fn has_root_hash_state(&self, root_hash: &Digest<H>) -> bool {
+        self.root_hash_states.contains_key(root_hash)
+    }
+
+    pub fn count_root_hash_states(&self) -> usize {
+        self.root_hash_states.len()
+    }
+
+    pub fn is_root_hash_evicted(&self, root_hash: &Digest<H>) -> bool {
+        self.evicted_root_hashes.contains(root_hash)
+    }
+
+    /// Returns false if the root hash has no state yet and the sender has already opened
+    /// `MAX_ROOT_HASHES_PER_SENDER` root hashes.
+    pub fn try_open_root_hash(&mut self, sender_id: &ID, root_hash: &Digest<H>) -> bool {
+        if self.root_hash_states.contains_key(root_hash) {
+            return true;
+        }
+        let opened = self
+            .opened_root_hashes
+            .entry(sender_id.clone())
+            .or_default();
+        if opened.len() >= MAX_ROOT_HASHES_PER_SENDER {
+            return false;
+        }
+        opened.insert(*root_hash);
+        true
+    }
+
+    /// Drops the state of every root hash that can no longer gather enough `Echo` and `Ready`
+    /// messages, given that only the first `Echo` and `Ready` of each sender count.
+    pub fn evict_unreachable_root_hashes(&mut self) {
+        let size = self.validator_set.size();
+        let pending_echo_count = size - self.echo_root_hashes.len();
+        let pending_ready_count = size - self.ready_root_hashes.len();
+        let max_durable_faulty_size = self.validator_set.max_durable_faulty_size();
+        let data_shard_count = self.validator_set.as_encoder().data_shard_count();
+        let unreachable: Vec<Digest<H>> = self
+            .root_hash_states
+            .iter()
+            .filter(|(_, root_hash_state)| {
+                root_hash_state.count_received_ready_messages() + pending_ready_count
+                    <= 2 * max_durable_faulty_size
+                    || root_hash_state.count_received_echo_messages() + pending_echo_count
+                        < data_shard_count
+            })
+            .map(|(root_hash, _)| *root_hash)
+            .collect();
+        for root_hash in unreachable {
+            self.root_hash_states.remove(&root_hash);
+            self.evicted_root_hashes.insert(root_hash);
+        }
+    }
+
+    /// Drops the state of a root hash whose shards don't decode to a valid payload.
+    pub fn evict_root_hash(&mut self, root_hash: &Digest<H>) {
+        self.root_hash_states.remove(root_hash);
+        self.evicted_root_hashes.insert(*root_hash);
+    }
+
+    pub fn get_echo_root_hash(&self, sender_id: &ID) -> Option<&Digest<H>> {
+        self.echo_root_hashes.get(sender_id)
+    }
+
+    pub fn insert_echo_root_hash(&mut self, sender_id: ID, root_hash: Digest<H>) {
+        self.echo_root_hashes.entry(sender_id).or_insert(root_hash);
+    }
+
+    pub fn get_ready_root_hash(&self, sender_id: &ID) -> Option<&Digest<H>> {
+        self.ready_root_hashes.get(sender_id)
+    }
+
+    pub fn insert_ready_root_hash(&mut self, sender_id: ID, root_hash: Digest<H>) {
+        self.ready_root_hashes.entry(sender_id).or_insert(root_hash);
+    }
+
+    pub fn count_echo_messages(&self, digest: &Digest<H>) -> usize {
         self.get_root_hash_state(digest)
             .count_received_echo_messages()
     }
 
-    pub fn count_ready_messages(&self, digest: &Digest) -> usize {
+    pub fn count_shards(&self, digest: &Digest<H>) -> usize {
+        self.get_root_hash_state(digest).count_received_shards()
+    }
+
+    pub fn count_ready_messages(&self, digest: &Digest<H>) -> usize {
         self.get_root_hash_state(digest)
             .count_received_ready_messages()
     }
 
-    pub fn validate_proof(&self, proof: &Proof<Vec<u8>>, node_id: &ID) -> bool {
+    /// Returns true if the proof is the one of the node and of the given Merkle tree version.
+    pub fn validate_proof(
+        &self,
+        proof: &Proof<Vec<u8>, H>,
+        node_id: &ID,
+        version: MerkleTreeVersion,
+    ) -> bool {
         self.validator_set.index(node_id).map(Into::into) == Some(proof.index())
-            && proof.validate(self.validator_set.size())
+            && proof.validate_with_version(self.validator_set.size(), version)
     }
 
-    pub fn can_compute_output(&self, root_hash: &Digest) -> bool {
+    /// In plain mode, the value is known as soon as a single `Value` or `Echo` has been received.
+    pub fn can_compute_output(&self, root_hash: &Digest<H>) -> bool {
         self.count_ready_messages(root_hash) > 2 * self.validator_set.max_durable_faulty_size()
-            && self.count_echo_messages(root_hash)
-                >= self.validator_set.as_encoder().data_shard_count()
+            && (self.get_root_hash_state(root_hash).get_value().is_some()
+                || self.count_shards(root_hash)
+                    >= self.validator_set.as_encoder().data_shard_count())
     }
 
-    pub fn fault_logs(&self) -> &Vec<FaultLog<ID>> {
+    pub fn fault_logs(&self) -> &Vec<FaultLog<ID, SID, H>> {
         &self.fault_logs
     }
 
-    pub fn push_fault_log(&mut self, fault_log: FaultLog<ID>) {
+    pub fn push_fault_log(&mut self, fault_log: FaultLog<ID, SID, H>) {
         self.fault_logs.push(fault_log);
     }
 
@@ -97,7 +226,49 @@ impl<ID: NodeId, IDX: ValidatorIndex> ReliableBroadcastState<ID, IDX> {
         self.output.as_ref()
     }
 
-    pub fn into_output_and_logs(self) -> (Option<Vec<u8>>, Vec<FaultLog<ID>>) {
+    pub fn into_output_and_logs(self) -> OutputAndFaultLogs<ID, SID, H> {
         (self.output, self.fault_logs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_hashes_per_sender_are_limited() {
+        let validator_indices = (1u16..=4).map(|id| (id, id as usize - 1)).collect();
+        let validator_set = ValidatorSet::new(validator_indices).unwrap();
+        let mut state: ReliableBroadcastState<_, _, _> =
+            ReliableBroadcastState::new(validator_set, "test");
+        for i in 0..MAX_ROOT_HASHES_PER_SENDER as u8 {
+            assert!(state.try_open_root_hash(&4, &[i; 32]));
+            state.get_or_init_mut_root_hash_state([i; 32]);
+        }
+        assert!(!state.try_open_root_hash(&4, &[u8::MAX; 32]));
+        // root hashes opened by others can still be referred to.
+        assert!(state.try_open_root_hash(&4, &[0; 32]));
+        assert!(state.try_open_root_hash(&1, &[u8::MAX; 32]));
+    }
+
+    #[test]
+    fn test_unreachable_root_hashes_are_evicted() {
+        let validator_indices = (1u16..=4).map(|id| (id, id as usize - 1)).collect();
+        let validator_set = ValidatorSet::new(validator_indices).unwrap();
+        let mut state: ReliableBroadcastState<_, _, _> =
+            ReliableBroadcastState::new(validator_set, "test");
+        state.get_or_init_mut_root_hash_state([0; 32]);
+        state.get_or_init_mut_root_hash_state([1; 32]);
+        for id in 1..=2 {
+            state.insert_ready_root_hash(id, [1; 32]);
+            state
+                .get_or_init_mut_root_hash_state([1; 32])
+                .insert_received_ready_message(id);
+        }
+        state.evict_unreachable_root_hashes();
+        // only two senders are left for [0; 32], while 2f + 1 = 3 `Ready` messages are required.
+        assert!(state.is_root_hash_evicted(&[0; 32]));
+        assert!(!state.is_root_hash_evicted(&[1; 32]));
+        assert_eq!(1, state.count_root_hash_states());
+    }
+}
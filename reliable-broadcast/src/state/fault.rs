@@ -1,18 +1,28 @@
-use crate::{message::BroadcastMessage, node::NodeId};
+use crate::{
+    merkle::{MerkleHasher, Sha3Hasher},
+    message::BroadcastMessage,
+    node::NodeId,
+    session::SessionId,
+};
 
 #[derive(Debug, Clone)]
 pub enum FaultType {
     UnknownSender,
+    SessionMismatched,
     ReceivedValueFromNonProposer,
     MultipleValueMessages,
     MultipleEchoMessages,
     MultipleReadyMessages,
     InvalidProof,
+    RootHashLimitExceeded,
+    UnrequestedShardResponse,
+    PayloadTooLarge,
+    InvalidEncoding,
 }
 
 #[derive(Debug, Clone)]
-pub struct FaultLog<ID: NodeId> {
+pub struct FaultLog<ID: NodeId, SID: SessionId, H: MerkleHasher = Sha3Hasher> {
     pub sender_id: ID,
-    pub message: BroadcastMessage,
+    pub message: BroadcastMessage<SID, H>,
     pub fault_type: FaultType,
 }
@@ -1,8 +1,11 @@
-use crate::{merkle::Proof, node::NodeId};
+use crate::{
+    merkle::{MerkleHasher, Proof},
+    node::NodeId,
+};
 use std::collections::{BTreeMap, BTreeSet};
 
 /// Reliable Broadcast State by Root Hash
-pub struct RootHashState<ID: NodeId> {
+pub struct RootHashState<ID: NodeId, H: MerkleHasher> {
     /// value message sender ID.
     proposer: Option<ID>,
 
@@ -10,7 +13,35 @@ pub struct RootHashState<ID: NodeId> {
     echo_sent: bool,
 
     /// The proofs we have received via `Echo` messages, by sender ID.
-    received_echo_messages: BTreeMap<ID, Proof<Vec<u8>>>,
+    received_echo_messages: BTreeMap<ID, Proof<Vec<u8>, H>>,
+
+    /// The senders of `EchoHash` messages whose full `Echo` hasn't been received.
+    received_echo_hash_messages: BTreeSet<ID>,
+
+    /// The proof we echo, kept to send the shard to the nodes it has been withheld from or which
+    /// request it.
+    echo_proof: Option<Proof<Vec<u8>, H>>,
+
+    /// The nodes we have sent an `EchoHash` instead of the full `Echo`.
+    echo_hash_targets: BTreeSet<ID>,
+
+    /// Whether we have already multicast `CanDecode`.
+    can_decode_sent: bool,
+
+    /// The senders of `CanDecode` messages.
+    received_can_decode_messages: BTreeSet<ID>,
+
+    /// Whether we have already multicast `ShardRequest`.
+    shard_request_sent: bool,
+
+    /// The nodes whose `ShardRequest` we have answered.
+    answered_shard_requests: BTreeSet<ID>,
+
+    /// The full value, in plain mode.
+    value: Option<Vec<u8>>,
+
+    /// The senders of plain mode `Echo` messages.
+    received_plain_echo_messages: BTreeSet<ID>,
 
     /// Whether we have already multicast `Ready`.
     ready_sent: bool,
@@ -19,19 +50,28 @@ pub struct RootHashState<ID: NodeId> {
     received_ready_messages: BTreeSet<ID>,
 }
 
-impl<ID: NodeId> Default for RootHashState<ID> {
+impl<ID: NodeId, H: MerkleHasher> Default for RootHashState<ID, H> {
     fn default() -> Self {
         Self {
             proposer: None,
             echo_sent: false,
             received_echo_messages: BTreeMap::new(),
+            received_echo_hash_messages: BTreeSet::new(),
+            echo_proof: None,
+            echo_hash_targets: BTreeSet::new(),
+            can_decode_sent: false,
+            received_can_decode_messages: BTreeSet::new(),
+            shard_request_sent: false,
+            answered_shard_requests: BTreeSet::new(),
+            value: None,
+            received_plain_echo_messages: BTreeSet::new(),
             ready_sent: false,
             received_ready_messages: BTreeSet::new(),
         }
     }
 }
 
-impl<ID: NodeId> RootHashState<ID> {
+impl<ID: NodeId, H: MerkleHasher> RootHashState<ID, H> {
     pub fn get_proposer(&self) -> Option<&ID> {
         self.proposer.as_ref()
     }
@@ -48,16 +88,93 @@ impl<ID: NodeId> RootHashState<ID> {
         self.echo_sent = true
     }
 
-    pub fn get_received_echo_message(&self, node_id: &ID) -> Option<&Proof<Vec<u8>>> {
+    pub fn get_received_echo_message(&self, node_id: &ID) -> Option<&Proof<Vec<u8>, H>> {
         self.received_echo_messages.get(node_id)
     }
 
-    pub fn insert_received_echo_message(&mut self, node_id: ID, proof: Proof<Vec<u8>>) {
+    pub fn insert_received_echo_message(&mut self, node_id: ID, proof: Proof<Vec<u8>, H>) {
+        self.received_echo_hash_messages.remove(&node_id);
         self.received_echo_messages.insert(node_id, proof);
     }
 
+    /// Returns false if the sender's `EchoHash` or full `Echo` has already been received.
+    pub fn insert_received_echo_hash_message(&mut self, node_id: ID) -> bool {
+        !self.received_echo_messages.contains_key(&node_id)
+            && self.received_echo_hash_messages.insert(node_id)
+    }
+
+    pub fn get_echo_proof(&self) -> Option<&Proof<Vec<u8>, H>> {
+        self.echo_proof.as_ref()
+    }
+
+    pub fn set_echo_proof(&mut self, proof: Proof<Vec<u8>, H>) {
+        self.echo_proof = Some(proof)
+    }
+
+    pub fn insert_echo_hash_target(&mut self, node_id: ID) {
+        self.echo_hash_targets.insert(node_id);
+    }
+
+    /// Returns the nodes an `EchoHash` has been sent to and which haven't announced they can
+    /// decode, and forgets about them.
+    pub fn take_echo_hash_targets(&mut self) -> Vec<ID> {
+        let targets = core::mem::take(&mut self.echo_hash_targets);
+        targets
+            .into_iter()
+            .filter(|node_id| !self.received_can_decode_messages.contains(node_id))
+            .collect()
+    }
+
+    pub fn is_can_decode_sent(&self) -> bool {
+        self.can_decode_sent
+    }
+
+    pub fn turn_can_decode_sent_on(&mut self) {
+        self.can_decode_sent = true
+    }
+
+    pub fn insert_received_can_decode_message(&mut self, node_id: ID) -> bool {
+        self.received_can_decode_messages.insert(node_id)
+    }
+
+    pub fn is_can_decode_received(&self, node_id: &ID) -> bool {
+        self.received_can_decode_messages.contains(node_id)
+    }
+
+    pub fn is_shard_request_sent(&self) -> bool {
+        self.shard_request_sent
+    }
+
+    pub fn turn_shard_request_sent_on(&mut self) {
+        self.shard_request_sent = true
+    }
+
+    /// Returns false if the node's `ShardRequest` has already been answered.
+    pub fn insert_answered_shard_request(&mut self, node_id: ID) -> bool {
+        self.answered_shard_requests.insert(node_id)
+    }
+
+    /// Returns the number of shards received via full `Echo` or `ShardResponse` messages.
+    pub fn count_received_shards(&self) -> usize {
+        self.received_echo_messages.len()
+    }
+
+    pub fn get_value(&self) -> Option<&Vec<u8>> {
+        self.value.as_ref()
+    }
+
+    pub fn set_value(&mut self, value: Vec<u8>) {
+        self.value = Some(value)
+    }
+
+    pub fn insert_received_plain_echo_message(&mut self, node_id: ID) -> bool {
+        self.received_plain_echo_messages.insert(node_id)
+    }
+
     pub fn count_received_echo_messages(&self) -> usize {
         self.received_echo_messages.len()
+            + self.received_echo_hash_messages.len()
+            + self.received_plain_echo_messages.len()
     }
 
     pub fn is_ready_sent(&self) -> bool {
@@ -1,13 +1,16 @@
 use logger::prelude::*;
-use reliable_broadcast::message::BroadcastMessage;
+use reliable_broadcast::merkle::{MerkleTree, Sha3Hasher};
+use reliable_broadcast::message::{BroadcastMessage, BroadcastMessageContent};
+use reliable_broadcast::mode::{BroadcastMode, EchoMode};
 use reliable_broadcast::node::NodeMessage;
 use reliable_broadcast::validator::ValidatorSet;
-use reliable_broadcast::ReliableBroadcast;
+use reliable_broadcast::{Error, FaultType, ReliableBroadcast, MAX_PAYLOAD_SIZE};
 use std::collections::BTreeMap;
 use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 use std::{fmt, thread};
 
 type Id = u16;
+type SessionId = String;
 
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug, Hash)]
 struct Index(u64);
@@ -39,8 +42,11 @@ impl fmt::Display for Index {
 struct TestNode {
     id: Id,
     index: Index,
-    message_receiver: Receiver<NodeMessage<Id>>,
-    message_router: BTreeMap<Id, SyncSender<NodeMessage<Id>>>,
+    message_receiver: Receiver<NodeMessage<Id, SessionId>>,
+    message_router: BTreeMap<Id, SyncSender<NodeMessage<Id, SessionId>>>,
+    broadcast_mode: BroadcastMode,
+    echo_mode: EchoMode,
+    max_payload_size: usize,
 }
 
 impl fmt::Debug for TestNode {
@@ -52,12 +58,26 @@ impl fmt::Debug for TestNode {
 impl ReliableBroadcast for TestNode {
     type NodeId = Id;
     type ValidatorIndex = Index;
+    type SessionId = SessionId;
+    type Hasher = Sha3Hasher;
 
     fn my_id(&self) -> &Id {
         &self.id
     }
 
-    fn next_message(&self) -> NodeMessage<Id> {
+    fn broadcast_mode(&self) -> BroadcastMode {
+        self.broadcast_mode
+    }
+
+    fn echo_mode(&self) -> EchoMode {
+        self.echo_mode
+    }
+
+    fn max_payload_size(&self) -> usize {
+        self.max_payload_size
+    }
+
+    fn next_message(&self) -> NodeMessage<Id, SessionId> {
         let message = self.message_receiver.recv().unwrap();
         let sender_id = match &message {
             NodeMessage::BroadcastMessage {
@@ -70,11 +90,17 @@ impl ReliableBroadcast for TestNode {
         message
     }
 
-    fn send_message(&self, target_id: Id, message: BroadcastMessage) {
-        let message_type = match message {
-            BroadcastMessage::Value(_) => "value message",
-            BroadcastMessage::Echo(_) => "echo message",
-            BroadcastMessage::Ready(_) => "ready message",
+    fn send_message(&self, target_id: Id, message: BroadcastMessage<SessionId>) {
+        let message_type = match message.content {
+            BroadcastMessageContent::Value(_) => "value message",
+            BroadcastMessageContent::Echo(_) => "echo message",
+            BroadcastMessageContent::Ready(_) => "ready message",
+            BroadcastMessageContent::PlainValue(_) => "plain value message",
+            BroadcastMessageContent::PlainEcho(_) => "plain echo message",
+            BroadcastMessageContent::EchoHash(_) => "echo hash message",
+            BroadcastMessageContent::CanDecode(_) => "can decode message",
+            BroadcastMessageContent::ShardRequest(_) => "shard request message",
+            BroadcastMessageContent::ShardResponse(_) => "shard response message",
         };
         debug!(
             "[send message]{} -> {}: {}",
@@ -90,18 +116,11 @@ impl ReliableBroadcast for TestNode {
     }
 }
 
-#[test]
-fn test_simple_procedure() {
-    // init logger
-    let mut builder = logger::default::DefaultLoggerBuilder::new();
-    builder.is_async(true);
-    builder.level(logger::Level::Debug);
-    let _logger = builder.build();
-
+fn init_network(size: Id) -> BTreeMap<Id, TestNode> {
     let channel_size = 10000;
-    let mut message_receivers: BTreeMap<Id, Receiver<NodeMessage<Id>>> = BTreeMap::new();
-    let mut message_router: BTreeMap<Id, SyncSender<NodeMessage<Id>>> = BTreeMap::new();
-    for id in 1..=4 {
+    let mut message_receivers: BTreeMap<Id, Receiver<NodeMessage<Id, SessionId>>> = BTreeMap::new();
+    let mut message_router: BTreeMap<Id, SyncSender<NodeMessage<Id, SessionId>>> = BTreeMap::new();
+    for id in 1..=size {
         let (sender, receiver) = sync_channel(channel_size);
         message_receivers.insert(id, receiver);
         message_router.insert(id, sender);
@@ -115,20 +134,37 @@ fn test_simple_procedure() {
                 index: (id - 1).into(),
                 message_receiver,
                 message_router: message_router.clone(),
+                broadcast_mode: BroadcastMode::default(),
+                echo_mode: EchoMode::default(),
+                max_payload_size: MAX_PAYLOAD_SIZE,
             },
         );
     }
+    nodes
+}
+
+#[test]
+fn test_simple_procedure() {
+    // init logger
+    let mut builder = logger::default::DefaultLoggerBuilder::new();
+    builder.is_async(true);
+    builder.level(logger::Level::Debug);
+    let _logger = builder.build();
+
+    let nodes = init_network(4);
     let input = b"Foo";
     let proposer_id = 1;
+    let session_id: SessionId = "test-1".to_owned();
     let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
     let validator_set = ValidatorSet::new(validator_indices).unwrap();
     let mut handles = BTreeMap::new();
     for (id, node) in nodes {
         let validator_set = validator_set.clone();
+        let session_id = session_id.clone();
         let handle = if id == proposer_id {
-            thread::spawn(move || node.propose(input.to_vec(), validator_set))
+            thread::spawn(move || node.propose(input.to_vec(), validator_set, session_id))
         } else {
-            thread::spawn(move || node.execute(None, validator_set))
+            thread::spawn(move || node.execute(None, validator_set, session_id))
         };
         handles.insert(id, handle);
     }
@@ -146,3 +182,540 @@ fn test_simple_procedure() {
         }
     }
 }
+
+#[test]
+fn test_plain_procedure() {
+    let input = b"Vote";
+    // the input is short enough for both modes to disseminate it in plain
+    for broadcast_mode in [
+        BroadcastMode::Plain,
+        BroadcastMode::Auto {
+            max_plain_size: input.len(),
+        },
+    ] {
+        let mut nodes = init_network(4);
+        for node in nodes.values_mut() {
+            node.broadcast_mode = broadcast_mode;
+        }
+        let proposer_id = 2;
+        let session_id: SessionId = "test-plain".to_owned();
+        let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+        let validator_set = ValidatorSet::new(validator_indices).unwrap();
+        let mut handles = BTreeMap::new();
+        for (id, node) in nodes {
+            let validator_set = validator_set.clone();
+            let session_id = session_id.clone();
+            let handle = thread::spawn(move || {
+                let result = if id == proposer_id {
+                    node.propose(input.to_vec(), validator_set, session_id)
+                } else {
+                    node.execute(None, validator_set, session_id)
+                };
+                (result, node)
+            });
+            handles.insert(id, handle);
+        }
+        let results: Vec<_> = handles
+            .into_iter()
+            .map(|(id, handle)| (id, handle.join().unwrap()))
+            .collect();
+        for (id, (result, _node)) in results {
+            match result {
+                Ok(state) => {
+                    assert!(state.is_decided());
+                    let output = state.get_output().unwrap().as_slice();
+                    assert_eq!(input, output);
+                    assert!(state.fault_logs().is_empty());
+                }
+                Err(err) => {
+                    panic!("id: {:?}, err: {:?}", id, err);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hash_echo_procedure() {
+    let mut nodes = init_network(7);
+    for node in nodes.values_mut() {
+        node.echo_mode = EchoMode::Hash;
+    }
+    // the silent node withholds the full echoes of nodes 1 to 3, so they need the remaining ones.
+    let silent_id = 7;
+    let _silent_node = nodes.remove(&silent_id).unwrap();
+    let input = b"Foo";
+    let proposer_id = 4;
+    let session_id: SessionId = "test-hash-echo".to_owned();
+    let validator_indices = (1..=7).map(|id: Id| (id, (id - 1).into())).collect();
+    let validator_set = ValidatorSet::new(validator_indices).unwrap();
+    let mut handles = BTreeMap::new();
+    for (id, node) in nodes {
+        let validator_set = validator_set.clone();
+        let session_id = session_id.clone();
+        let handle = thread::spawn(move || {
+            let result = if id == proposer_id {
+                node.propose(input.to_vec(), validator_set, session_id)
+            } else {
+                node.execute(None, validator_set, session_id)
+            };
+            (result, node)
+        });
+        handles.insert(id, handle);
+    }
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|(id, handle)| (id, handle.join().unwrap()))
+        .collect();
+    for (id, (result, _node)) in results {
+        match result {
+            Ok(state) => {
+                assert!(state.is_decided());
+                let output = state.get_output().unwrap().as_slice();
+                assert_eq!(input, output);
+                assert!(state.fault_logs().is_empty());
+            }
+            Err(err) => {
+                panic!("id: {:?}, err: {:?}", id, err);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_late_node_retrieves_shards() {
+    let mut nodes = init_network(7);
+    let late_node = nodes.remove(&7).unwrap();
+    let input = b"Foo";
+    let proposer_id = 1;
+    let session_id: SessionId = "test-late".to_owned();
+    let validator_indices = (1..=7).map(|id: Id| (id, (id - 1).into())).collect();
+    let validator_set = ValidatorSet::new(validator_indices).unwrap();
+    let mut handles = BTreeMap::new();
+    for (id, node) in nodes {
+        let validator_set = validator_set.clone();
+        let session_id = session_id.clone();
+        let handle = thread::spawn(move || {
+            let mut state = if id == proposer_id {
+                node.propose(input.to_vec(), validator_set, session_id)
+            } else {
+                node.execute(None, validator_set, session_id)
+            }
+            .unwrap();
+            node.serve_shard_requests(&mut state).unwrap();
+            state
+        });
+        handles.insert(id, handle);
+    }
+
+    // the late node misses every `Value` and `Echo` message. A `Ready` is always sent after the
+    // `Value` or `Echo` of the same sender, so none of them is left once all the `Ready` are in.
+    let mut ready_messages = Vec::new();
+    while ready_messages.len() < handles.len() {
+        let node_message = late_node.message_receiver.recv().unwrap();
+        if let NodeMessage::BroadcastMessage { message, .. } = &node_message {
+            if let BroadcastMessageContent::Ready(_) = message.content {
+                ready_messages.push(node_message);
+            }
+        }
+    }
+    let router = late_node.message_router.clone();
+    for node_message in ready_messages {
+        router[&late_node.id].send(node_message).unwrap();
+    }
+    let state = late_node
+        .execute(None, validator_set.clone(), session_id)
+        .unwrap();
+    assert!(state.is_decided());
+    assert_eq!(input, state.get_output().unwrap().as_slice());
+    assert!(state.fault_logs().is_empty());
+
+    for (id, handle) in handles {
+        router[&id].send(NodeMessage::Terminate).unwrap();
+        let state = handle.join().unwrap();
+        assert_eq!(input, state.get_output().unwrap().as_slice());
+    }
+}
+
+#[test]
+fn test_oversized_payloads_are_rejected() {
+    let max_payload_size = 8;
+    let mut nodes = init_network(4);
+    for node in nodes.values_mut() {
+        node.max_payload_size = max_payload_size;
+    }
+    let session_id: SessionId = "test-oversized".to_owned();
+    let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+    let validator_set = ValidatorSet::new(validator_indices).unwrap();
+
+    let result = nodes[&1].propose(vec![0; 9], validator_set.clone(), session_id.clone());
+    assert!(matches!(
+        result,
+        Err(Error::PayloadTooLarge {
+            size: 9,
+            max_size: 8
+        })
+    ));
+
+    // a faulty proposer sends shards too long for any payload of at most 8 bytes.
+    let faulty_id = 1;
+    let target_id = 2;
+    let target_node = nodes.remove(&target_id).unwrap();
+    let proof = MerkleTree::new(vec![vec![0u8; 64]; 4])
+        .proof(target_node.index.into())
+        .unwrap();
+    let sender = &target_node.message_router[&target_id];
+    for content in [
+        BroadcastMessageContent::Value(proof.into()),
+        BroadcastMessageContent::PlainValue(vec![0u8; 9].into()),
+    ] {
+        sender
+            .send(NodeMessage::BroadcastMessage {
+                sender_id: faulty_id,
+                message: BroadcastMessage {
+                    session_id: session_id.clone(),
+                    content,
+                },
+            })
+            .unwrap();
+    }
+    sender.send(NodeMessage::Terminate).unwrap();
+    let state = target_node
+        .execute(None, validator_set, session_id)
+        .unwrap();
+    assert!(!state.is_decided());
+    assert_eq!(0, state.count_root_hash_states());
+    assert_eq!(2, state.fault_logs().len());
+    for log in state.fault_logs() {
+        assert_eq!(faulty_id, log.sender_id);
+        assert!(matches!(log.fault_type, FaultType::PayloadTooLarge));
+    }
+}
+
+#[test]
+fn test_lying_payload_length_is_rejected() {
+    let max_payload_size = 8;
+    let mut nodes = init_network(4);
+    for node in nodes.values_mut() {
+        node.max_payload_size = max_payload_size;
+    }
+    let session_id: SessionId = "test-lying-length".to_owned();
+    let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+    let validator_set = ValidatorSet::new(validator_indices).unwrap();
+
+    // a faulty proposer encodes 4 bytes, but claims a payload of 100 bytes: its shards are short
+    // enough, the lie only shows once they are decoded.
+    let faulty_id = 1;
+    let target_id = 2;
+    let coder = validator_set.as_encoder();
+    let shard_len = 4;
+    let mut value = vec![0, 0, 0, 100];
+    value.extend_from_slice(b"Lies");
+    value.resize(shard_len * nodes.len(), 0);
+    let mut shards: Vec<&mut [u8]> = value.chunks_mut(shard_len).collect();
+    coder.encode(&mut shards).unwrap();
+    let tree =
+        MerkleTree::<_, Sha3Hasher>::new(shards.iter().map(|shard| shard.to_vec()).collect());
+    let root_hash = *tree.root_hash();
+
+    let target_node = nodes.remove(&target_id).unwrap();
+    let sender = &target_node.message_router[&target_id];
+    let mut messages = vec![(
+        faulty_id,
+        BroadcastMessageContent::Value(tree.proof(target_node.index.into()).unwrap().into()),
+    )];
+    for (id, node) in &nodes {
+        let proof = tree.proof(node.index.into()).unwrap();
+        messages.push((*id, BroadcastMessageContent::Echo(proof.into())));
+    }
+    for id in nodes.keys() {
+        messages.push((*id, BroadcastMessageContent::Ready(root_hash.into())));
+    }
+    for (sender_id, content) in messages {
+        sender
+            .send(NodeMessage::BroadcastMessage {
+                sender_id,
+                message: BroadcastMessage {
+                    session_id: session_id.clone(),
+                    content,
+                },
+            })
+            .unwrap();
+    }
+    sender.send(NodeMessage::Terminate).unwrap();
+    let state = target_node
+        .execute(None, validator_set, session_id)
+        .unwrap();
+    assert!(!state.is_decided());
+    assert_eq!(0, state.count_root_hash_states());
+    assert!(state.is_root_hash_evicted(&root_hash));
+    assert_eq!(1, state.fault_logs().len());
+    let log = &state.fault_logs()[0];
+    assert_eq!(faulty_id, log.sender_id);
+    assert!(matches!(log.fault_type, FaultType::PayloadTooLarge));
+}
+
+#[test]
+fn test_large_committee_procedure() {
+    // more than 256 shards, so the value is coded over GF(2^16).
+    let nodes = init_network(260);
+    let input = b"Foo";
+    let proposer_id = 1;
+    let session_id: SessionId = "test-large".to_owned();
+    let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+    let validator_set = ValidatorSet::new(validator_indices).unwrap();
+    let mut handles = BTreeMap::new();
+    for (id, node) in nodes {
+        let validator_set = validator_set.clone();
+        let session_id = session_id.clone();
+        let handle = thread::spawn(move || {
+            let result = if id == proposer_id {
+                node.propose(input.to_vec(), validator_set, session_id)
+            } else {
+                node.execute(None, validator_set, session_id)
+            };
+            (result, node)
+        });
+        handles.insert(id, handle);
+    }
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|(id, handle)| (id, handle.join().unwrap()))
+        .collect();
+    for (id, (result, _node)) in results {
+        match result {
+            Ok(state) => {
+                assert!(state.is_decided());
+                let output = state.get_output().unwrap().as_slice();
+                assert_eq!(input, output);
+            }
+            Err(err) => {
+                panic!("id: {:?}, err: {:?}", id, err);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_session_mismatched_messages_are_rejected() {
+    let nodes = init_network(4);
+    let input = b"Bar";
+    let proposer_id = 1;
+    let session_id: SessionId = "test-2".to_owned();
+    // every node receives Ready messages of another instance from all the others first.
+    for node in nodes.values() {
+        for (target_id, sender) in &node.message_router {
+            if *target_id != node.id {
+                sender
+                    .send(NodeMessage::BroadcastMessage {
+                        sender_id: node.id,
+                        message: BroadcastMessage {
+                            session_id: "test-other".to_owned(),
+                            content: BroadcastMessageContent::Ready([0u8; 32].into()),
+                        },
+                    })
+                    .unwrap();
+            }
+        }
+    }
+    let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+    let validator_set = ValidatorSet::new(validator_indices).unwrap();
+    let mut handles = BTreeMap::new();
+    for (id, node) in nodes {
+        let validator_set = validator_set.clone();
+        let session_id = session_id.clone();
+        let handle = if id == proposer_id {
+            thread::spawn(move || node.propose(input.to_vec(), validator_set, session_id))
+        } else {
+            thread::spawn(move || node.execute(None, validator_set, session_id))
+        };
+        handles.insert(id, handle);
+    }
+    for (id, handle) in handles {
+        let result = handle.join().unwrap();
+        match result {
+            Ok(state) => {
+                assert!(state.is_decided());
+                let output = state.get_output().unwrap().as_slice();
+                assert_eq!(input, output);
+                let mismatched = state
+                    .fault_logs()
+                    .iter()
+                    .filter(|log| matches!(log.fault_type, FaultType::SessionMismatched))
+                    .count();
+                assert_eq!(3, mismatched);
+            }
+            Err(err) => {
+                panic!("id: {:?}, err: {:?}", id, err);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_conflicting_echo_and_ready_messages_are_rejected() {
+    let nodes = init_network(7);
+    let input = b"Baz";
+    let proposer_id = 1;
+    let faulty_id = 7;
+    let session_id: SessionId = "test-3".to_owned();
+    // the faulty node sends Echo and Ready for two different root hashes to every other node first.
+    let faulty_index: usize = nodes[&faulty_id].index.into();
+    let forged_proofs: Vec<_> = [b"forged-1", b"forged-2"]
+        .iter()
+        .map(|value| {
+            MerkleTree::<_, Sha3Hasher>::new(vec![value.to_vec(); nodes.len()])
+                .proof(faulty_index)
+                .unwrap()
+        })
+        .collect();
+    for (target_id, sender) in &nodes[&faulty_id].message_router {
+        if *target_id == faulty_id {
+            continue;
+        }
+        for proof in &forged_proofs {
+            let contents = [
+                BroadcastMessageContent::Echo(proof.clone().into()),
+                BroadcastMessageContent::Ready((*proof.root_hash()).into()),
+            ];
+            for content in contents {
+                sender
+                    .send(NodeMessage::BroadcastMessage {
+                        sender_id: faulty_id,
+                        message: BroadcastMessage {
+                            session_id: session_id.clone(),
+                            content,
+                        },
+                    })
+                    .unwrap();
+            }
+        }
+    }
+    let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+    let validator_set = ValidatorSet::new(validator_indices).unwrap();
+    let mut handles = BTreeMap::new();
+    for (id, node) in nodes {
+        let validator_set = validator_set.clone();
+        let session_id = session_id.clone();
+        // nodes are returned along with the result, so that no receiver is dropped while the
+        // others may still be sending messages to it.
+        let handle = thread::spawn(move || {
+            let result = if id == proposer_id {
+                node.propose(input.to_vec(), validator_set, session_id)
+            } else {
+                node.execute(None, validator_set, session_id)
+            };
+            (result, node)
+        });
+        handles.insert(id, handle);
+    }
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|(id, handle)| (id, handle.join().unwrap()))
+        .collect();
+    for (id, (result, _node)) in results {
+        match result {
+            Ok(state) => {
+                assert!(state.is_decided());
+                let output = state.get_output().unwrap().as_slice();
+                assert_eq!(input, output);
+                if id == faulty_id {
+                    continue;
+                }
+                let count_faults = |fault_type: fn(&FaultType) -> bool| {
+                    state
+                        .fault_logs()
+                        .iter()
+                        .filter(|log| fault_type(&log.fault_type))
+                        .inspect(|log| assert_eq!(faulty_id, log.sender_id))
+                        .count()
+                };
+                assert!(count_faults(|t| matches!(t, FaultType::MultipleEchoMessages)) >= 1);
+                assert!(count_faults(|t| matches!(t, FaultType::MultipleReadyMessages)) >= 1);
+            }
+            Err(err) => {
+                panic!("id: {:?}, err: {:?}", id, err);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_root_hash_flood_is_bounded() {
+    let nodes = init_network(7);
+    let input = b"Qux";
+    let proposer_id = 1;
+    let faulty_id = 7;
+    let flood_size: u8 = 100;
+    let session_id: SessionId = "test-4".to_owned();
+    // the faulty node floods every other node with Echo and Ready messages for made-up root hashes.
+    for (target_id, sender) in &nodes[&faulty_id].message_router {
+        if *target_id == faulty_id {
+            continue;
+        }
+        let target_index: usize = nodes[target_id].index.into();
+        for i in 0..flood_size {
+            let proof = MerkleTree::new(vec![vec![i]; nodes.len()])
+                .proof(target_index)
+                .unwrap();
+            let contents = [
+                BroadcastMessageContent::Echo(proof.into()),
+                BroadcastMessageContent::Ready([i; 32].into()),
+            ];
+            for content in contents {
+                sender
+                    .send(NodeMessage::BroadcastMessage {
+                        sender_id: faulty_id,
+                        message: BroadcastMessage {
+                            session_id: session_id.clone(),
+                            content,
+                        },
+                    })
+                    .unwrap();
+            }
+        }
+    }
+    let validator_indices = nodes.iter().map(|(id, node)| (*id, node.index)).collect();
+    let validator_set = ValidatorSet::new(validator_indices).unwrap();
+    let mut handles = BTreeMap::new();
+    for (id, node) in nodes {
+        let validator_set = validator_set.clone();
+        let session_id = session_id.clone();
+        let handle = thread::spawn(move || {
+            let result = if id == proposer_id {
+                node.propose(input.to_vec(), validator_set, session_id)
+            } else {
+                node.execute(None, validator_set, session_id)
+            };
+            (result, node)
+        });
+        handles.insert(id, handle);
+    }
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|(id, handle)| (id, handle.join().unwrap()))
+        .collect();
+    for (id, (result, _node)) in results {
+        match result {
+            Ok(state) => {
+                assert!(state.is_decided());
+                let output = state.get_output().unwrap().as_slice();
+                assert_eq!(input, output);
+                if id == faulty_id {
+                    continue;
+                }
+                // every made-up root hash is unreachable once the honest nodes are ready.
+                assert_eq!(1, state.count_root_hash_states());
+                let rejected = state
+                    .fault_logs()
+                    .iter()
+                    .inspect(|log| assert_eq!(faulty_id, log.sender_id))
+                    .count();
+                assert!(rejected >= 2 * (flood_size as usize - 1));
+            }
+            Err(err) => {
+                panic!("id: {:?}, err: {:?}", id, err);
+            }
+        }
+    }
+}
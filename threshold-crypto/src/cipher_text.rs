@@ -1,20 +1,42 @@
-use crate::hasher;
-use bls12_381::{pairing, G1Affine, G1Projective, G2Projective};
+use crate::{hasher, Error};
+use bls12_381::{pairing, G1Affine, G1Projective, G2Affine, G2Projective};
 use core::hash;
 use group::Curve;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
-pub struct Ciphertext(
+/// Version of the serialized `Ciphertext`. Version 1 added the label and the version itself: a
+/// ciphertext serialized before, as `(U, V, W)`, can't be deserialized anymore, but rebuilt from
+/// its parts with `Ciphertext::new` it verifies as it used to.
+pub const CIPHERTEXT_VERSION: u8 = 1;
+
+/// A threshold encrypted message `(U, V, W)`, bound to a label, empty if none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ciphertext(G1Projective, Vec<u8>, G2Projective, Vec<u8>);
+
+/// The serialized layout of `Ciphertext`, prefixed with `CIPHERTEXT_VERSION`.
+#[derive(Deserialize, Serialize)]
+struct VersionedCiphertext(
+    u8,
     #[serde(with = "crate::serializers::g1_projective")] G1Projective,
     Vec<u8>,
     #[serde(with = "crate::serializers::g2_projective")] G2Projective,
+    Vec<u8>,
 );
 
 impl Ciphertext {
     pub fn new(g1: G1Projective, msg: Vec<u8>, g2: G2Projective) -> Self {
-        Self(g1, msg, g2)
+        Self(g1, msg, g2, Vec::new())
+    }
+
+    /// Creates a ciphertext bound to a label, such as the context it may be decrypted in.
+    pub fn new_with_label(
+        g1: G1Projective,
+        msg: Vec<u8>,
+        g2: G2Projective,
+        label: Vec<u8>,
+    ) -> Self {
+        Self(g1, msg, g2, label)
     }
 
     pub fn as_g1(&self) -> &G1Projective {
@@ -29,21 +51,54 @@ impl Ciphertext {
         &self.2
     }
 
+    /// The label the ciphertext was encrypted with, empty if none. Callers must check it is the
+    /// expected one: `verify` only checks that it hasn't been changed since encryption.
+    pub fn as_label(&self) -> &[u8] {
+        &self.3
+    }
+
+    /// Returns the G2 element `W` is the multiple of, binding `U`, `V` and the label.
+    /// `hash_with_g1_and_label` hashes an empty label as `hash_with_g1`, so that unlabeled
+    /// ciphertexts verify as before.
+    pub(crate) fn hash_to_g2(&self) -> G2Affine {
+        hasher::hash_with_g1_and_label(self.0.to_affine(), &self.1, &self.3)
+    }
+
     /// Returns `true` if this is a valid ciphertext. This check is necessary to prevent
     /// chosen-ciphertext attacks.
     pub fn verify(&self) -> bool {
-        let Ciphertext(ref u, ref v, ref w) = *self;
-        let hash = hasher::hash_with_g1(u.to_affine(), v);
+        let Ciphertext(ref u, _, ref w, _) = *self;
+        let hash = self.hash_to_g2();
         pairing(&G1Affine::generator(), &w.to_affine()) == pairing(&u.to_affine(), &hash)
     }
 }
 
+impl Serialize for Ciphertext {
+    fn serialize<S: Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        let Ciphertext(u, v, w, label) = self.clone();
+        VersionedCiphertext(CIPHERTEXT_VERSION, u, v, w, label).serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ciphertext {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        let VersionedCiphertext(version, u, v, w, label) = Deserialize::deserialize(d)?;
+        if version != CIPHERTEXT_VERSION {
+            return Err(DeserializeError::custom(
+                Error::UnsupportedCiphertextVersion(version),
+            ));
+        }
+        Ok(Self(u, v, w, label))
+    }
+}
+
 impl hash::Hash for Ciphertext {
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
-        let Ciphertext(ref u, ref v, ref w) = *self;
+        let Ciphertext(ref u, ref v, ref w, ref label) = *self;
         u.to_affine().to_compressed().as_ref().hash(state);
         v.hash(state);
         w.to_affine().to_compressed().as_ref().hash(state);
+        label.hash(state);
     }
 }
 
@@ -55,8 +110,8 @@ impl PartialOrd for Ciphertext {
 
 impl Ord for Ciphertext {
     fn cmp(&self, other: &Self) -> Ordering {
-        let Ciphertext(ref u0, ref v0, ref w0) = self;
-        let Ciphertext(ref u1, ref v1, ref w1) = other;
+        let Ciphertext(ref u0, ref v0, ref w0, ref label0) = self;
+        let Ciphertext(ref u1, ref v1, ref w1, ref label1) = other;
 
         let mine = u0.to_affine().to_compressed();
         let others = u1.to_affine().to_compressed();
@@ -68,5 +123,6 @@ impl Ord for Ciphertext {
                 let others = w1.to_affine().to_compressed();
                 mine.as_ref().cmp(others.as_ref())
             })
+            .then_with(|| label0.cmp(label1))
     }
 }
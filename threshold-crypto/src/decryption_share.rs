@@ -1,8 +1,10 @@
+use crate::{encoding, serializers, Result, PK_SIZE};
 use bls12_381::G1Projective;
-use core::{fmt, hash};
+use core::{borrow::Borrow, fmt, hash};
 use group::{Curve, Group};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
 
 /// A decryption share. A threshold of decryption shares can be used to decrypt a message.
 #[derive(Clone, PartialEq, Eq)]
@@ -12,6 +14,17 @@ impl DecryptionShare {
     pub fn new(projective: G1Projective) -> Self {
         Self(projective)
     }
+
+    /// Returns the compressed encoding of the decryption share.
+    pub fn to_bytes(&self) -> [u8; PK_SIZE] {
+        encoding::g1_to_bytes(&self.0)
+    }
+
+    /// Decodes a decryption share, rejecting points outside the prime order subgroup and the
+    /// identity.
+    pub fn from_bytes<B: Borrow<[u8; PK_SIZE]>>(bytes: B) -> Result<Self> {
+        encoding::nonidentity_g1_from_bytes(bytes.borrow()).map(Self)
+    }
 }
 
 impl Distribution<DecryptionShare> for Standard {
@@ -37,3 +50,16 @@ impl AsRef<G1Projective> for DecryptionShare {
         &self.0
     }
 }
+
+impl Serialize for DecryptionShare {
+    fn serialize<S: Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        serializers::fixed_bytes::serialize(&self.to_bytes(), s)
+    }
+}
+
+impl<'de> Deserialize<'de> for DecryptionShare {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        let bytes: [u8; PK_SIZE] = serializers::fixed_bytes::deserialize(d)?;
+        Self::from_bytes(bytes).map_err(DeserializeError::custom)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{Error, Result, PK_SIZE, SIG_SIZE, SK_SIZE};
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use group::Curve;
+
+/// Decodes a compressed G1 point, checking that it lies in the prime order subgroup.
+pub(crate) fn g1_from_bytes(bytes: &[u8; PK_SIZE]) -> Result<G1Projective> {
+    Option::<G1Affine>::from(G1Affine::from_compressed(bytes))
+        .map(Into::into)
+        .ok_or(Error::InvalidPoint)
+}
+
+pub(crate) fn g1_to_bytes(g1: &G1Projective) -> [u8; PK_SIZE] {
+    g1.to_affine().to_compressed()
+}
+
+/// Decodes a compressed G1 point, rejecting the identity.
+pub(crate) fn nonidentity_g1_from_bytes(bytes: &[u8; PK_SIZE]) -> Result<G1Projective> {
+    let g1 = g1_from_bytes(bytes)?;
+    if bool::from(g1.is_identity()) {
+        return Err(Error::IdentityOrZero);
+    }
+    Ok(g1)
+}
+
+/// Decodes a compressed G2 point, checking that it lies in the prime order subgroup and rejecting
+/// the identity.
+pub(crate) fn nonidentity_g2_from_bytes(bytes: &[u8; SIG_SIZE]) -> Result<G2Projective> {
+    let g2: G2Projective = Option::<G2Affine>::from(G2Affine::from_compressed(bytes))
+        .map(Into::into)
+        .ok_or(Error::InvalidPoint)?;
+    if bool::from(g2.is_identity()) {
+        return Err(Error::IdentityOrZero);
+    }
+    Ok(g2)
+}
+
+pub(crate) fn g2_to_bytes(g2: &G2Projective) -> [u8; SIG_SIZE] {
+    g2.to_affine().to_compressed()
+}
+
+/// Decodes a canonical little-endian scalar, rejecting zero.
+pub(crate) fn nonzero_scalar_from_bytes(bytes: &[u8; SK_SIZE]) -> Result<Scalar> {
+    let scalar = Option::<Scalar>::from(Scalar::from_bytes(bytes)).ok_or(Error::InvalidScalar)?;
+    if scalar == Scalar::zero() {
+        return Err(Error::IdentityOrZero);
+    }
+    Ok(scalar)
+}
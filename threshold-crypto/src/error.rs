@@ -4,6 +4,38 @@ use thiserror::Error as ThisError;
 pub enum Error {
     #[error("Not enough signature shares")]
     NotEnoughShares,
+    #[error("Not enough valid shares, invalid indices: {invalid_indices:?}")]
+    NotEnoughValidShares { invalid_indices: Vec<u64> },
     #[error("Signature shares contain a duplicated index")]
     DuplicateEntry,
+    #[error("Bytes don't encode a point in the prime order subgroup")]
+    InvalidPoint,
+    #[error("Bytes don't encode a canonical scalar")]
+    InvalidScalar,
+    #[error("The identity or zero is not valid here")]
+    IdentityOrZero,
+    #[error("Authentication tag doesn't match")]
+    InvalidTag,
+    #[error("The constant coefficient of a refresh commitment must be the identity")]
+    NonZeroRefreshConstant,
+    #[error("Threshold mismatch: expected {expected}, got {actual}")]
+    ThresholdMismatch { expected: usize, actual: usize },
+    #[error("Reshare commitment {0} doesn't match the dealer's public key share")]
+    InvalidReshareCommitment(u64),
+    #[error("The secret key share doesn't match the public key shares")]
+    InvalidSecretKeyShare,
+    #[error("Unsupported keystore version, KDF or cipher")]
+    UnsupportedKeystore,
+    #[error("Scrypt parameters exceed the supported maximum")]
+    ScryptParamsTooLarge,
+    #[error("The keystore was saved for other public key shares")]
+    KeystoreMismatch,
+    #[error("Keystore I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Keystore JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported ciphertext version: {0}")]
+    UnsupportedCiphertextVersion(u8),
+    #[error("Invalid encoding length: {0}")]
+    InvalidLength(usize),
 }
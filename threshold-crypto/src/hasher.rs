@@ -1,10 +1,20 @@
+#[cfg(feature = "hash-to-curve")]
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
 use bls12_381::{G1Affine, G2Affine, G2Projective};
 use group::{Curve, Group};
 use rand::{distributions::Standard, Rng};
 use rand_chacha::{rand_core::SeedableRng, ChaChaRng};
 use tiny_keccak::{Hasher, Sha3};
 
-fn sha3_256(data: &[u8]) -> [u8; 32] {
+/// Domain separation tag of the BLS signature scheme with signatures in G2, for
+/// `hash_to_g2_rfc9380`.
+#[cfg(feature = "hash-to-curve")]
+pub const SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Appended to the input of `hash_with_g1_and_label` with a label.
+const LABEL_TAG: &[u8] = b"THRESHOLD-CRYPTO-LABEL";
+
+pub(crate) fn sha3_256(data: &[u8]) -> [u8; 32] {
     let mut output = [0; 32];
     let mut sha3 = Sha3::v256();
     sha3.update(data);
@@ -20,6 +30,15 @@ pub fn hash<M: AsRef<[u8]>>(msg: M) -> G2Affine {
     projective.to_affine()
 }
 
+/// convert bytes message to G2 element with hash_to_curve as specified by RFC 9380, suite
+/// BLS12381G2_XMD:SHA-256_SSWU_RO_, tagged with the given domain separation tag
+#[cfg(feature = "hash-to-curve")]
+pub fn hash_to_g2_rfc9380<M: AsRef<[u8]>>(msg: M, dst: &[u8]) -> G2Affine {
+    let projective =
+        <G2Projective as HashToCurve<ExpandMsgXmd<sha2::Sha256>>>::hash_to_curve(msg, dst);
+    projective.to_affine()
+}
+
 /// convert bytes message & G1 element into G2 element
 pub fn hash_with_g1<M: AsRef<[u8]>>(g1: G1Affine, msg: M) -> G2Affine {
     let mut msg = if msg.as_ref().len() > 64 {
@@ -31,6 +50,26 @@ pub fn hash_with_g1<M: AsRef<[u8]>>(g1: G1Affine, msg: M) -> G2Affine {
     hash(&msg)
 }
 
+/// convert bytes message, label & G1 element into G2 element. An empty label is hashed as
+/// `hash_with_g1`. Otherwise, the G1 element, the digests of the message and the label and
+/// `LABEL_TAG` are hashed: more than the 112 bytes `hash_with_g1` ever hashes, so that no labeled
+/// and unlabeled inputs are hashed alike.
+pub fn hash_with_g1_and_label<M: AsRef<[u8]>, L: AsRef<[u8]>>(
+    g1: G1Affine,
+    msg: M,
+    label: L,
+) -> G2Affine {
+    let label = label.as_ref();
+    if label.is_empty() {
+        return hash_with_g1(g1, msg);
+    }
+    let mut bytes = g1.to_compressed().to_vec();
+    bytes.extend(sha3_256(msg.as_ref()));
+    bytes.extend(sha3_256(label));
+    bytes.extend(LABEL_TAG);
+    hash(&bytes)
+}
+
 pub fn xor_with_hash(g1: G1Affine, bytes: &[u8]) -> Vec<u8> {
     let digest = sha3_256(g1.to_compressed().as_ref());
     let rand_core = ChaChaRng::from_seed(digest);
@@ -46,6 +85,7 @@ pub fn xor_with_hash(g1: G1Affine, bytes: &[u8]) -> Vec<u8> {
 mod tests {
     use super::*;
     use bls12_381::G1Projective;
+    use group::Group;
     use rand::distributions::Standard;
     use rand::{thread_rng, Rng};
 
@@ -75,6 +115,60 @@ mod tests {
         assert_ne!(hash_with_g1(g0, &msg), hash_with_g1(g1, &msg));
     }
 
+    #[test]
+    fn test_hash_with_g1_and_label() {
+        let mut rng = thread_rng();
+        let g0 = G1Projective::random(&mut rng).to_affine();
+        let msg = b"msg";
+        assert_eq!(hash_with_g1(g0, msg), hash_with_g1_and_label(g0, msg, b""));
+        let labeled = hash_with_g1_and_label(g0, msg, b"label");
+        assert_ne!(labeled, hash_with_g1_and_label(g0, msg, b"other"));
+        assert_ne!(labeled, hash_with_g1_and_label(g0, b"msglabel", b""));
+        assert_ne!(labeled, hash_with_g1(g0, b"msglabel"));
+    }
+
+    // test vectors of RFC 9380, appendix J.10.1
+    #[cfg(feature = "hash-to-curve")]
+    #[test]
+    fn test_hash_to_g2_rfc9380() {
+        use hex_fmt::HexFmt;
+
+        const DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        let cases: [(&[u8], [&str; 4]); 3] = [
+            (
+                b"",
+                [
+                    "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+                    "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+                    "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+                    "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+                ],
+            ),
+            (
+                b"abc",
+                [
+                    "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8",
+                    "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+                    "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16",
+                    "1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+                ],
+            ),
+            (
+                b"abcdef0123456789",
+                [
+                    "190d119345b94fbd15497bcba94ecf7db2cbfd1e1fe7da034d26cbba169fb3968288b3fafb265f9ebd380512a71c3f2c",
+                    "121982811d2491fde9ba7ed31ef9ca474f0e1501297f68c298e9f4c0028add35aea8bb83d53c08cfc007c1e005723cd0",
+                    "0bb5e7572275c567462d91807de765611490205a941a5a6af3b1691bfe596c31225d3aabdf15faff860cb4ef17c7c3be",
+                    "05571a0f8d3c08d094576981f4a3b8eda0a8e771fcdcc8ecceaf1356a6acf17574518acb506e435b639353c2e14827c8",
+                ],
+            ),
+        ];
+        for (msg, expected) in cases {
+            let uncompressed = hash_to_g2_rfc9380(msg, DST).to_uncompressed();
+            assert_eq!(expected.concat(), format!("{}", HexFmt(uncompressed)));
+        }
+    }
+
     #[test]
     fn test_xor_with_hash() {
         let mut rng = thread_rng();
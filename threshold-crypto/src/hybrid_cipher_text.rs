@@ -0,0 +1,80 @@
+use crate::{Ciphertext, Error, Result};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use group::Curve;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// Size of the symmetric key encrypted with the threshold public key.
+pub const HYBRID_KEY_SIZE: usize = 32;
+
+/// A ciphertext of the KEM-DEM scheme: only a random ChaCha20-Poly1305 key is threshold
+/// encrypted, and the payload is encrypted and authenticated with that key.
+///
+/// As every key encrypts a single payload, the nonce is always zero.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HybridCiphertext {
+    key_ciphertext: Ciphertext,
+    payload: Vec<u8>,
+}
+
+impl HybridCiphertext {
+    pub(crate) fn seal(
+        key: &[u8; HYBRID_KEY_SIZE],
+        key_ciphertext: Ciphertext,
+        msg: &[u8],
+    ) -> Self {
+        let aad = associated_data(&key_ciphertext);
+        let payload = ChaCha20Poly1305::new(Key::from_slice(key))
+            .encrypt(&Nonce::default(), Payload { msg, aad: &aad })
+            .expect("payload fits in a ChaCha20-Poly1305 message");
+        Self {
+            key_ciphertext,
+            payload,
+        }
+    }
+
+    /// Decrypts the payload with the decrypted symmetric key, checking its authentication tag.
+    pub(crate) fn open(&self, mut key: Vec<u8>) -> Result<Vec<u8>> {
+        if key.len() != HYBRID_KEY_SIZE {
+            return Err(Error::InvalidLength(key.len()));
+        }
+        let aad = associated_data(&self.key_ciphertext);
+        let msg = ChaCha20Poly1305::new(Key::from_slice(&key)).decrypt(
+            &Nonce::default(),
+            Payload {
+                msg: &self.payload,
+                aad: &aad,
+            },
+        );
+        key.zeroize();
+        msg.map_err(|_| Error::InvalidTag)
+    }
+
+    /// The threshold encrypted symmetric key, to compute decryption shares of.
+    pub fn as_key_ciphertext(&self) -> &Ciphertext {
+        &self.key_ciphertext
+    }
+
+    /// The encrypted payload, followed by its authentication tag.
+    pub fn as_payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Returns `true` if the key ciphertext is valid. The payload is only authenticated when
+    /// decrypted.
+    pub fn verify(&self) -> bool {
+        self.key_ciphertext.verify()
+    }
+}
+
+/// Binds the payload to the ciphertext of its key.
+fn associated_data(key_ciphertext: &Ciphertext) -> Vec<u8> {
+    let mut aad = key_ciphertext.as_g1().to_affine().to_compressed().to_vec();
+    aad.extend(key_ciphertext.as_msg());
+    aad.extend(key_ciphertext.as_g2().to_affine().to_compressed().as_ref());
+    aad.extend(key_ciphertext.as_label());
+    aad
+}
@@ -0,0 +1,218 @@
+use crate::{Error, PublicKeyShares, Result, SecretKeyShare, SK_SIZE};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::{rngs::OsRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::{fs, io::Write, ops::Deref, path::Path};
+use zeroize::Zeroizing;
+
+/// Version of the keystore format written by `Keystore::encrypt`.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+const KDF: &str = "scrypt";
+const CIPHER: &str = "chacha20poly1305";
+const SALT_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+
+// Largest scrypt cost parameters a key is derived with, so that a crafted keystore can't make
+// `decrypt` allocate gigabytes of memory or run for hours.
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_R: u32 = 8;
+const MAX_SCRYPT_P: u32 = 1;
+
+/// Cost parameters of the scrypt key derivation.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        Self {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+struct KeystoreCrypto {
+    kdf: String,
+    kdf_params: ScryptParams,
+    #[serde(with = "hex::serde")]
+    salt: Vec<u8>,
+    cipher: String,
+    #[serde(with = "hex::serde")]
+    nonce: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    ciphertext: Vec<u8>,
+}
+
+/// A secret key share encrypted with a password, to be stored as JSON.
+///
+/// The key share is encrypted with ChaCha20-Poly1305 under a key derived from the password with
+/// scrypt. The node index and the fingerprint of the public key shares are stored in clear but
+/// authenticated, and a share is only loaded for the public key shares it belongs to.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Keystore {
+    version: u32,
+    index: u64,
+    #[serde(with = "hex::serde")]
+    fingerprint: Vec<u8>,
+    crypto: KeystoreCrypto,
+}
+
+impl Keystore {
+    /// Encrypts the `index`-th key share of the public key shares with the password.
+    pub fn encrypt(
+        share: &SecretKeyShare,
+        index: u64,
+        public_key_shares: &PublicKeyShares,
+        password: &[u8],
+    ) -> Result<Self> {
+        Self::encrypt_with_params_and_rng(
+            &mut OsRng,
+            ScryptParams::default(),
+            share,
+            index,
+            public_key_shares,
+            password,
+        )
+    }
+
+    pub fn encrypt_with_params_and_rng<R: Rng>(
+        rng: &mut R,
+        params: ScryptParams,
+        share: &SecretKeyShare,
+        index: u64,
+        public_key_shares: &PublicKeyShares,
+        password: &[u8],
+    ) -> Result<Self> {
+        if !public_key_shares.verify_secret_key_share(index, share) {
+            return Err(Error::InvalidSecretKeyShare);
+        }
+        let fingerprint = public_key_shares.fingerprint().to_vec();
+        let mut salt = vec![0; SALT_SIZE];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = vec![0; NONCE_SIZE];
+        rng.fill_bytes(&mut nonce);
+
+        let key = derive_key(password, &salt, &params)?;
+        let msg = Zeroizing::new(share.to_bytes());
+        let aad = associated_data(KEYSTORE_VERSION, index, &fingerprint);
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: msg.as_ref(),
+                    aad: &aad,
+                },
+            )
+            .expect("key share fits in a ChaCha20-Poly1305 message");
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            index,
+            fingerprint,
+            crypto: KeystoreCrypto {
+                kdf: KDF.to_string(),
+                kdf_params: params,
+                salt,
+                cipher: CIPHER.to_string(),
+                nonce,
+                ciphertext,
+            },
+        })
+    }
+
+    /// Decrypts the key share with the password, and checks that it is the `index`-th key share of
+    /// the public key shares.
+    pub fn decrypt(
+        &self,
+        password: &[u8],
+        public_key_shares: &PublicKeyShares,
+    ) -> Result<SecretKeyShare> {
+        if self.version != KEYSTORE_VERSION
+            || self.crypto.kdf != KDF
+            || self.crypto.cipher != CIPHER
+        {
+            return Err(Error::UnsupportedKeystore);
+        }
+        if self.crypto.nonce.len() != NONCE_SIZE {
+            return Err(Error::InvalidLength(self.crypto.nonce.len()));
+        }
+        if self.fingerprint != public_key_shares.fingerprint() {
+            return Err(Error::KeystoreMismatch);
+        }
+
+        let key = derive_key(password, &self.crypto.salt, &self.crypto.kdf_params)?;
+        let aad = associated_data(self.version, self.index, &self.fingerprint);
+        let msg = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+            .decrypt(
+                Nonce::from_slice(&self.crypto.nonce),
+                Payload {
+                    msg: &self.crypto.ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| Error::InvalidTag)?;
+        let bytes: Zeroizing<[u8; SK_SIZE]> = Zeroizing::new(
+            msg.as_slice()
+                .try_into()
+                .map_err(|_| Error::InvalidLength(msg.len()))?,
+        );
+        // borrowed, so that the key share isn't copied out of `Zeroizing`
+        let share = SecretKeyShare::from_bytes(bytes.deref())?;
+        if !public_key_shares.verify_secret_key_share(self.index, &share) {
+            return Err(Error::InvalidSecretKeyShare);
+        }
+        Ok(share)
+    }
+
+    /// The index of the stored key share.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Writes the keystore as JSON to a new file, only readable by its owner on Unix.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_vec_pretty(self)?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        file.write_all(&json)?;
+        Ok(file.sync_all()?)
+    }
+
+    /// Reads a keystore saved with `save`. The key share is only decrypted by `decrypt`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = fs::read(path)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+fn derive_key(password: &[u8], salt: &[u8], params: &ScryptParams) -> Result<Zeroizing<[u8; 32]>> {
+    if params.log_n > MAX_SCRYPT_LOG_N || params.r > MAX_SCRYPT_R || params.p > MAX_SCRYPT_P {
+        return Err(Error::ScryptParamsTooLarge);
+    }
+    let params = scrypt::Params::new(params.log_n, params.r, params.p)
+        .map_err(|_| Error::UnsupportedKeystore)?;
+    let mut key = Zeroizing::new([0; 32]);
+    scrypt::scrypt(password, salt, &params, key.as_mut()).expect("32 bytes is a valid key length");
+    Ok(key)
+}
+
+/// Binds the ciphertext to the metadata stored in clear.
+fn associated_data(version: u32, index: u64, fingerprint: &[u8]) -> Vec<u8> {
+    let mut aad = version.to_be_bytes().to_vec();
+    aad.extend(index.to_be_bytes());
+    aad.extend(fingerprint);
+    aad
+}
@@ -2,17 +2,33 @@ mod error;
 pub use error::Error;
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// Size of the compressed encoding of a G1 point: public keys and decryption shares.
+pub const PK_SIZE: usize = 48;
+/// Size of the compressed encoding of a G2 point: signatures.
+pub const SIG_SIZE: usize = 96;
+/// Size of the encoding of a scalar: secret keys.
+pub const SK_SIZE: usize = 32;
+
 mod cipher_text;
 mod decryption_share;
+mod encoding;
 pub mod hasher;
+mod hybrid_cipher_text;
+mod keystore;
 pub mod polynomial;
 mod public_key;
+mod refresh;
+mod reshare;
 mod secret_key;
 mod serializers;
 mod signature;
 
-pub use cipher_text::Ciphertext;
+pub use cipher_text::{Ciphertext, CIPHERTEXT_VERSION};
 pub use decryption_share::DecryptionShare;
+pub use hybrid_cipher_text::{HybridCiphertext, HYBRID_KEY_SIZE};
+pub use keystore::{Keystore, ScryptParams, KEYSTORE_VERSION};
 pub use public_key::{PublicKey, PublicKeyShare, PublicKeyShares};
+pub use refresh::{RefreshCommitment, RefreshDealing, RefreshShare};
+pub use reshare::{ReshareCommitment, ReshareDealing, ReshareShare};
 pub use secret_key::{SecretKey, SecretKeyShare, SecretKeyShares};
 pub use signature::{Signature, SignatureShare};
@@ -1,4 +1,4 @@
-use bls12_381::{G1Projective, Scalar};
+use bls12_381::{G1Affine, G1Projective, Scalar};
 use group::Curve;
 use std::cmp::Ordering;
 use std::ops::{AddAssign, MulAssign};
@@ -21,6 +21,10 @@ impl Commitment {
         self.coefficients[index]
     }
 
+    pub(crate) fn coefficients(&self) -> &[G1Projective] {
+        &self.coefficients
+    }
+
     pub fn evaluate(&self, x: Scalar) -> G1Projective {
         if self.coefficients.len() == 0 {
             // return zero
@@ -34,6 +38,12 @@ impl Commitment {
             result
         }
     }
+
+    /// Returns whether `value` is the evaluation at `x` of the committed polynomial: Feldman's
+    /// check, `G * value == sum(C_k * x^k)`.
+    pub fn verify_evaluation(&self, x: Scalar, value: Scalar) -> bool {
+        G1Affine::generator() * value == self.evaluate(x)
+    }
 }
 
 impl PartialOrd<Self> for Commitment {
@@ -3,14 +3,19 @@ mod public_key_shares;
 pub use public_key_share::PublicKeyShare;
 pub use public_key_shares::PublicKeyShares;
 
-use crate::{hasher, Ciphertext, Signature};
+use crate::{
+    encoding, hasher, serializers, Ciphertext, HybridCiphertext, Result, Signature,
+    HYBRID_KEY_SIZE, PK_SIZE,
+};
 use bls12_381::{pairing, G1Affine, G1Projective, G2Affine, Scalar};
-use core::{fmt, hash};
+use core::{borrow::Borrow, fmt, hash};
 use group::{ff::Field, Curve};
 use hex_fmt::HexFmt;
 use rand::{rngs::OsRng, Rng};
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
 use std::ops::Mul;
+use zeroize::Zeroize;
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct PublicKey(G1Projective);
@@ -20,6 +25,16 @@ impl PublicKey {
         Self(projective)
     }
 
+    /// Returns the compressed encoding of the key.
+    pub fn to_bytes(&self) -> [u8; PK_SIZE] {
+        encoding::g1_to_bytes(&self.0)
+    }
+
+    /// Decodes a key, rejecting points outside the prime order subgroup and the identity.
+    pub fn from_bytes<B: Borrow<[u8; PK_SIZE]>>(bytes: B) -> Result<Self> {
+        encoding::nonidentity_g1_from_bytes(bytes.borrow()).map(Self)
+    }
+
     pub fn verify_with_hash(&self, sig: &Signature, hash: G2Affine) -> bool {
         let g1 = self.as_ref().into();
         let g2 = hash;
@@ -34,19 +49,69 @@ impl PublicKey {
         self.verify_with_hash(sig, hasher::hash(msg))
     }
 
+    /// Verifies a signature of the message hashed to G2 as specified by RFC 9380, with the given
+    /// domain separation tag.
+    #[cfg(feature = "hash-to-curve")]
+    pub fn verify_with_dst<M: AsRef<[u8]>>(&self, sig: &Signature, msg: M, dst: &[u8]) -> bool {
+        self.verify_with_hash(sig, hasher::hash_to_g2_rfc9380(msg, dst))
+    }
+
     pub fn encrypt<M: AsRef<[u8]>>(&self, msg: M) -> Ciphertext {
         self.encrypt_with_rng(&mut OsRng::default(), msg)
     }
 
     pub fn encrypt_with_rng<R: Rng, M: AsRef<[u8]>>(&self, rng: &mut R, msg: M) -> Ciphertext {
+        self.encrypt_with_label_and_rng(rng, msg, Vec::new())
+    }
+
+    /// Encrypts the message bound to the label, so that decryption shares are only valid for a
+    /// ciphertext with the same label.
+    pub fn encrypt_with_label<M: AsRef<[u8]>>(&self, msg: M, label: Vec<u8>) -> Ciphertext {
+        self.encrypt_with_label_and_rng(&mut OsRng, msg, label)
+    }
+
+    pub fn encrypt_with_label_and_rng<R: Rng, M: AsRef<[u8]>>(
+        &self,
+        rng: &mut R,
+        msg: M,
+        label: Vec<u8>,
+    ) -> Ciphertext {
         let r = Scalar::random(rng);
         let u = G1Affine::generator().mul(r);
         let v: Vec<u8> = {
             let g = self.0.to_affine().mul(r);
             hasher::xor_with_hash(g.to_affine(), msg.as_ref())
         };
-        let w = hasher::hash_with_g1(u.to_affine(), &v).mul(r);
-        Ciphertext::new(u, v, w)
+        let w = hasher::hash_with_g1_and_label(u.to_affine(), &v, &label).mul(r);
+        Ciphertext::new_with_label(u, v, w, label)
+    }
+
+    pub fn encrypt_hybrid<M: AsRef<[u8]>>(&self, msg: M) -> HybridCiphertext {
+        self.encrypt_hybrid_with_rng(&mut OsRng, msg)
+    }
+
+    /// Encrypts a random symmetric key with this key, and the message with that symmetric key, so
+    /// that only the former is hashed to G2 and checked with pairings, whatever the message size.
+    pub fn encrypt_hybrid_with_rng<R: Rng, M: AsRef<[u8]>>(
+        &self,
+        rng: &mut R,
+        msg: M,
+    ) -> HybridCiphertext {
+        self.encrypt_hybrid_with_label_and_rng(rng, msg, Vec::new())
+    }
+
+    /// Encrypts the symmetric key bound to the label, see `encrypt_with_label`.
+    pub fn encrypt_hybrid_with_label_and_rng<R: Rng, M: AsRef<[u8]>>(
+        &self,
+        rng: &mut R,
+        msg: M,
+        label: Vec<u8>,
+    ) -> HybridCiphertext {
+        let mut key: [u8; HYBRID_KEY_SIZE] = rng.gen();
+        let key_ciphertext = self.encrypt_with_label_and_rng(rng, key, label);
+        let ciphertext = HybridCiphertext::seal(&key, key_ciphertext, msg.as_ref());
+        key.zeroize();
+        ciphertext
     }
 }
 
@@ -87,3 +152,16 @@ impl Ord for PublicKey {
         }
     }
 }
+
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        serializers::fixed_bytes::serialize(&self.to_bytes(), s)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        let bytes: [u8; PK_SIZE] = serializers::fixed_bytes::deserialize(d)?;
+        Self::from_bytes(bytes).map_err(DeserializeError::custom)
+    }
+}
@@ -1,11 +1,12 @@
 use super::PublicKey;
-use crate::{hasher, Ciphertext, DecryptionShare, SignatureShare};
+use crate::{Ciphertext, DecryptionShare, Result, SignatureShare, PK_SIZE};
 use bls12_381::{pairing, G2Affine};
-use core::fmt;
+use core::{borrow::Borrow, fmt};
 use group::Curve;
 use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct PublicKeyShare(PublicKey);
 
 impl PublicKeyShare {
@@ -17,6 +18,16 @@ impl PublicKeyShare {
         self.0
     }
 
+    /// Returns the compressed encoding of the key share.
+    pub fn to_bytes(&self) -> [u8; PK_SIZE] {
+        self.0.to_bytes()
+    }
+
+    /// Decodes a key share, rejecting points outside the prime order subgroup and the identity.
+    pub fn from_bytes<B: Borrow<[u8; PK_SIZE]>>(bytes: B) -> Result<Self> {
+        PublicKey::from_bytes(bytes).map(Self)
+    }
+
     pub fn verify_with_hash(&self, sig: &SignatureShare, hash: G2Affine) -> bool {
         self.0.verify_with_hash(sig.as_ref(), hash)
     }
@@ -25,11 +36,21 @@ impl PublicKeyShare {
         self.0.verify(sig.as_ref(), msg)
     }
 
+    /// Verifies a signature share of the message hashed to G2 as specified by RFC 9380, with the
+    /// given domain separation tag.
+    #[cfg(feature = "hash-to-curve")]
+    pub fn verify_with_dst<M: AsRef<[u8]>>(
+        &self,
+        sig: &SignatureShare,
+        msg: M,
+        dst: &[u8],
+    ) -> bool {
+        self.0.verify_with_dst(sig.as_ref(), msg, dst)
+    }
+
     pub fn verify_decryption_share(&self, share: &DecryptionShare, ct: &Ciphertext) -> bool {
-        let g1 = ct.as_g1();
-        let msg = ct.as_msg();
         let g2 = ct.as_g2();
-        let hash = hasher::hash_with_g1(g1.to_affine(), msg);
+        let hash = ct.hash_to_g2();
         pairing(&share.as_ref().to_affine(), &hash)
             == pairing(&(self.0).as_ref().to_affine(), &g2.to_affine())
     }
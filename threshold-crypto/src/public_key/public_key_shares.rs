@@ -1,10 +1,13 @@
 use super::{PublicKey, PublicKeyShare};
 use crate::{
-    hasher, polynomial::Commitment, Ciphertext, DecryptionShare, Error, Result, Signature,
-    SignatureShare,
+    encoding, hasher, polynomial::Commitment, Ciphertext, DecryptionShare, Error, HybridCiphertext,
+    RefreshCommitment, ReshareCommitment, ReshareShare, Result, SecretKey, SecretKeyShare,
+    Signature, SignatureShare, PK_SIZE,
 };
-use bls12_381::Scalar;
+use bls12_381::{multi_miller_loop, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar};
 use group::Curve;
+use rand::{rngs::OsRng, Rng};
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
 use std::ops::{AddAssign, Mul, MulAssign, SubAssign};
 
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
@@ -23,12 +26,48 @@ impl PublicKeyShares {
         self.commit.degree()
     }
 
+    /// Returns the concatenated compressed encodings of the commitment coefficients, the master
+    /// public key first.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.commit
+            .coefficients()
+            .iter()
+            .flat_map(encoding::g1_to_bytes)
+            .collect()
+    }
+
+    /// Decodes the output of `to_bytes`, rejecting points outside the prime order subgroup and an
+    /// identity master public key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.is_empty() || bytes.len() % PK_SIZE != 0 {
+            return Err(Error::InvalidLength(bytes.len()));
+        }
+        let coefficients = bytes
+            .chunks_exact(PK_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let chunk = chunk.try_into().expect("chunks have the exact size");
+                if i == 0 {
+                    encoding::nonidentity_g1_from_bytes(chunk)
+                } else {
+                    encoding::g1_from_bytes(chunk)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(Commitment::new(coefficients)))
+    }
+
     /// Returns the master public key
     pub fn public_key(&self) -> PublicKey {
         let c0 = self.commit.get_coefficient(0);
         PublicKey::new(c0)
     }
 
+    /// Returns the SHA3-256 hash of the encoding, identifying the public key shares.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        hasher::sha3_256(&self.to_bytes())
+    }
+
     /// Returns the `i`-th public key share.
     pub fn public_key_share<T: Into<Scalar>>(&self, i: T) -> PublicKeyShare {
         let mut x = Scalar::one();
@@ -37,6 +76,111 @@ impl PublicKeyShares {
         PublicKeyShare::new(PublicKey::new(value))
     }
 
+    /// Returns whether the secret key share is the `i`-th one of the committed polynomial, e.g.
+    /// to check a share received from a dealer.
+    pub fn verify_secret_key_share<T: Into<Scalar>>(&self, i: T, share: &SecretKeyShare) -> bool {
+        let mut x = Scalar::one();
+        x.add_assign(i.into());
+        self.commit.verify_evaluation(x, **share.as_secret_key())
+    }
+
+    /// Returns the public key shares matching the secret key shares refreshed with the same
+    /// dealings. The master public key is unchanged.
+    pub fn refresh<'a, I>(&self, commitments: I) -> Result<Self>
+    where
+        I: Iterator<Item = &'a RefreshCommitment>,
+    {
+        let mut coefficients = self.commit.coefficients().to_vec();
+        for commitment in commitments {
+            if commitment.threshold() != self.threshold() {
+                return Err(Error::ThresholdMismatch {
+                    expected: self.threshold(),
+                    actual: commitment.threshold(),
+                });
+            }
+            if !bool::from(commitment.as_commitment().get_coefficient(0).is_identity()) {
+                return Err(Error::NonZeroRefreshConstant);
+            }
+            for (c, refresh) in coefficients
+                .iter_mut()
+                .zip(commitment.as_commitment().coefficients())
+            {
+                c.add_assign(refresh);
+            }
+        }
+        Ok(Self::new(Commitment::new(coefficients)))
+    }
+
+    /// Returns whether the reshare commitment deals the `i`-th key share.
+    pub fn verify_reshare_commitment<T: Into<Scalar>>(
+        &self,
+        i: T,
+        commitment: &ReshareCommitment,
+    ) -> bool {
+        let mut x = Scalar::one();
+        x.add_assign(i.into());
+        self.commit.evaluate(x) == commitment.as_commitment().get_coefficient(0)
+    }
+
+    /// Returns the public key shares of the new committee, from the reshare commitments of the
+    /// dealers whose reshare shares the new holders combine. The master public key is unchanged.
+    pub fn reshare<'a, I>(&self, commitments: I) -> Result<Self>
+    where
+        I: Iterator<Item = (u64, &'a ReshareCommitment)>,
+    {
+        let (indices, commitments): (Vec<u64>, Vec<&ReshareCommitment>) =
+            commitments.take(self.threshold() + 1).unzip();
+        if commitments.len() <= self.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        let new_threshold = commitments[0].threshold();
+        for (&i, commitment) in indices.iter().zip(&commitments) {
+            if commitment.threshold() != new_threshold {
+                return Err(Error::ThresholdMismatch {
+                    expected: new_threshold,
+                    actual: commitment.threshold(),
+                });
+            }
+            if !self.verify_reshare_commitment(i, commitment) {
+                return Err(Error::InvalidReshareCommitment(i));
+            }
+        }
+        let indices: Vec<Scalar> = indices.into_iter().map(Scalar::from).collect();
+        let mut coefficients = vec![G1Projective::identity(); new_threshold + 1];
+        for (l0, commitment) in lagrange_coefficients(&indices)?
+            .into_iter()
+            .zip(commitments)
+        {
+            for (c, dealt) in coefficients
+                .iter_mut()
+                .zip(commitment.as_commitment().coefficients())
+            {
+                c.add_assign(dealt * l0);
+            }
+        }
+        Ok(Self::new(Commitment::new(coefficients)))
+    }
+
+    /// Combines the reshare shares dealt to a new holder into its key share. The shares must have
+    /// been verified against the commitments passed to `reshare`, of the same dealers.
+    pub fn combine_reshare_shares<'a, I>(&self, shares: I) -> Result<SecretKeyShare>
+    where
+        I: Iterator<Item = (u64, &'a ReshareShare)>,
+    {
+        let (indices, shares): (Vec<Scalar>, Vec<&ReshareShare>) = shares
+            .take(self.threshold() + 1)
+            .map(|(i, share)| (Scalar::from(i), share))
+            .unzip();
+        if shares.len() <= self.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        let mut scalar = Scalar::zero();
+        for (l0, share) in lagrange_coefficients(&indices)?.into_iter().zip(shares) {
+            scalar.add_assign(share.as_scalar() * l0);
+        }
+        Ok(SecretKeyShare::new(SecretKey::new(scalar)))
+    }
+
     /// Combines the shares into a signature that can be verified with the main public key.
     pub fn combine_signatures<'a, I>(&self, shares: I) -> Result<Signature>
     where
@@ -56,6 +200,175 @@ impl PublicKeyShares {
         let g = interpolate(self.commit.degree(), samples)?;
         Ok(hasher::xor_with_hash(g.to_affine(), &ct.as_msg()))
     }
+
+    /// Decrypts the symmetric key with the shares, then the payload with the key, checking its
+    /// authentication tag.
+    pub fn decrypt_hybrid<'a, I>(&self, shares: I, ct: &HybridCiphertext) -> Result<Vec<u8>>
+    where
+        I: Iterator<Item = (u64, &'a DecryptionShare)>,
+    {
+        let key = self.decrypt(shares, ct.as_key_ciphertext())?;
+        ct.open(key)
+    }
+
+    /// Combines the shares into a signature of the message hash, skipping invalid shares. Returns
+    /// the signature and the indices of the shares found to be invalid.
+    ///
+    /// The first `threshold + 1` shares are combined optimistically. Only if the result doesn't
+    /// verify are all the shares verified, and `threshold + 1` valid ones combined instead.
+    pub fn combine_signatures_robust<'a, I>(
+        &self,
+        shares: I,
+        hash: G2Affine,
+    ) -> Result<(Signature, Vec<u64>)>
+    where
+        I: Iterator<Item = (u64, &'a SignatureShare)>,
+    {
+        let shares: Vec<_> = shares.collect();
+        if shares.len() <= self.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        let signature = self.combine_signatures(shares.iter().cloned())?;
+        if self.public_key().verify_with_hash(&signature, hash) {
+            return Ok((signature, Vec::new()));
+        }
+        let invalid_indices = self.verify_signature_shares(shares.iter().cloned(), hash);
+        let valid_shares = shares
+            .into_iter()
+            .filter(|(i, _)| !invalid_indices.contains(i));
+        match self.combine_signatures(valid_shares) {
+            Ok(signature) => Ok((signature, invalid_indices)),
+            Err(Error::NotEnoughShares) => Err(Error::NotEnoughValidShares { invalid_indices }),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Decrypts the ciphertext with the valid shares only. Returns the message and the indices of
+    /// the shares found to be invalid. All the shares are verified, at once.
+    pub fn decrypt_robust<'a, I>(&self, shares: I, ct: &Ciphertext) -> Result<(Vec<u8>, Vec<u64>)>
+    where
+        I: Iterator<Item = (u64, &'a DecryptionShare)>,
+    {
+        let shares: Vec<_> = shares.collect();
+        if shares.len() <= self.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        let invalid_indices = self.verify_decryption_shares(shares.iter().cloned(), ct);
+        let valid_shares = shares
+            .into_iter()
+            .filter(|(i, _)| !invalid_indices.contains(i));
+        match self.decrypt(valid_shares, ct) {
+            Ok(msg) => Ok((msg, invalid_indices)),
+            Err(Error::NotEnoughShares) => Err(Error::NotEnoughValidShares { invalid_indices }),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Verifies the signature shares of the same message hash at once, and returns the indices of
+    /// the invalid ones.
+    pub fn verify_signature_shares<'a, I>(&self, shares: I, hash: G2Affine) -> Vec<u64>
+    where
+        I: Iterator<Item = (u64, &'a SignatureShare)>,
+    {
+        self.verify_signature_shares_with_rng(&mut OsRng, shares, hash)
+    }
+
+    /// Checks `e(sum(r_i * PK_i), hash) == e(G, sum(r_i * S_i))` for random `r_i`, with a single
+    /// multi-pairing whatever the number of shares. If the check fails, the shares are verified
+    /// one by one to find the invalid ones.
+    pub fn verify_signature_shares_with_rng<'a, R, I>(
+        &self,
+        rng: &mut R,
+        shares: I,
+        hash: G2Affine,
+    ) -> Vec<u64>
+    where
+        R: Rng,
+        I: Iterator<Item = (u64, &'a SignatureShare)>,
+    {
+        let shares: Vec<_> = shares
+            .map(|(i, share)| (i, self.public_key_share(i), share))
+            .collect();
+        let mut public_key_sum = G1Projective::identity();
+        let mut signature_sum = G2Projective::identity();
+        for (_, public_key_share, share) in &shares {
+            let r = batch_coefficient(rng);
+            public_key_sum += public_key_share.into_inner().as_ref().mul(r);
+            signature_sum += share.as_ref().as_ref().mul(r);
+        }
+        if pairings_match(
+            public_key_sum,
+            hash,
+            G1Projective::generator(),
+            signature_sum,
+        ) {
+            return Vec::new();
+        }
+        shares
+            .into_iter()
+            .filter(|(_, public_key_share, share)| !public_key_share.verify_with_hash(share, hash))
+            .map(|(i, _, _)| i)
+            .collect()
+    }
+
+    /// Verifies the decryption shares of the same ciphertext at once, and returns the indices of
+    /// the invalid ones.
+    pub fn verify_decryption_shares<'a, I>(&self, shares: I, ct: &Ciphertext) -> Vec<u64>
+    where
+        I: Iterator<Item = (u64, &'a DecryptionShare)>,
+    {
+        self.verify_decryption_shares_with_rng(&mut OsRng, shares, ct)
+    }
+
+    /// Checks `e(sum(r_i * D_i), H(U, V)) == e(sum(r_i * PK_i), W)` for random `r_i`, with a
+    /// single multi-pairing whatever the number of shares. If the check fails, the shares are
+    /// verified one by one to find the invalid ones.
+    pub fn verify_decryption_shares_with_rng<'a, R, I>(
+        &self,
+        rng: &mut R,
+        shares: I,
+        ct: &Ciphertext,
+    ) -> Vec<u64>
+    where
+        R: Rng,
+        I: Iterator<Item = (u64, &'a DecryptionShare)>,
+    {
+        let shares: Vec<_> = shares
+            .map(|(i, share)| (i, self.public_key_share(i), share))
+            .collect();
+        let mut share_sum = G1Projective::identity();
+        let mut public_key_sum = G1Projective::identity();
+        for (_, public_key_share, share) in &shares {
+            let r = batch_coefficient(rng);
+            share_sum += share.as_ref().mul(r);
+            public_key_sum += public_key_share.into_inner().as_ref().mul(r);
+        }
+        let hash = ct.hash_to_g2();
+        if pairings_match(share_sum, hash, public_key_sum, *ct.as_g2()) {
+            return Vec::new();
+        }
+        shares
+            .into_iter()
+            .filter(|(_, public_key_share, share)| {
+                !public_key_share.verify_decryption_share(share, ct)
+            })
+            .map(|(i, _, _)| i)
+            .collect()
+    }
+}
+
+/// Returns a random 128-bit scalar, enough for a forged batch to pass with negligible probability.
+fn batch_coefficient<R: Rng>(rng: &mut R) -> Scalar {
+    Scalar::from_raw([rng.gen(), rng.gen(), 0, 0])
+}
+
+/// Returns whether `e(a0, b0) == e(a1, b1)`, sharing the final exponentiation of both pairings.
+fn pairings_match(a0: G1Projective, b0: G2Affine, a1: G1Projective, b1: G2Projective) -> bool {
+    let a0 = a0.to_affine();
+    let b0 = G2Prepared::from(b0);
+    let a1 = -a1.to_affine();
+    let b1 = G2Prepared::from(b1.to_affine());
+    multi_miller_loop(&[(&a0, &b0), (&a1, &b1)]).final_exponentiation() == Gt::identity()
 }
 
 impl From<Commitment> for PublicKeyShares {
@@ -64,46 +377,71 @@ impl From<Commitment> for PublicKeyShares {
     }
 }
 
+impl Serialize for PublicKeyShares {
+    fn serialize<S: Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        s.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKeyShares {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        let bytes: Vec<u8> = Deserialize::deserialize(d)?;
+        Self::from_bytes(&bytes).map_err(DeserializeError::custom)
+    }
+}
+
 fn interpolate<'a, I, C, A>(t: usize, items: I) -> Result<C>
 where
     I: Iterator<Item = (Scalar, &'a C)>,
     C: Curve<AffineRepr = A> + AddAssign<C>,
     A: Mul<Scalar, Output = C>,
 {
-    let samples: Vec<_> = items
-        .take(t + 1)
-        .map(|(i, sample)| {
-            let mut x = Scalar::one();
-            x.add_assign(i);
-            (x, sample)
-        })
-        .collect();
+    let (indices, samples): (Vec<Scalar>, Vec<&C>) = items.take(t + 1).unzip();
     if samples.len() <= t {
         return Err(Error::NotEnoughShares);
     }
     if t == 0 {
-        return Ok(*samples[0].1);
+        return Ok(*samples[0]);
+    }
+    let mut result = C::identity();
+    for (l0, sample) in lagrange_coefficients(&indices)?.into_iter().zip(samples) {
+        result.add_assign(sample.to_affine().mul(l0));
     }
+    Ok(result)
+}
+
+/// Returns the values at `0` of the Lagrange polynomials of the share indices: the `i`-th one is
+/// `1` at the `i`-th index and `0` at the others.
+fn lagrange_coefficients(indices: &[Scalar]) -> Result<Vec<Scalar>> {
+    let xs: Vec<Scalar> = indices
+        .iter()
+        .map(|i| {
+            let mut x = Scalar::one();
+            x.add_assign(i);
+            x
+        })
+        .collect();
+    let t = xs.len().saturating_sub(1);
     // Compute the products `x_prod[i]` of all but the `i`-th entry.
-    let mut x_prod: Vec<Scalar> = Vec::with_capacity(t);
+    let mut x_prod: Vec<Scalar> = Vec::with_capacity(t + 1);
     let mut tmp = Scalar::one();
     x_prod.push(tmp);
-    for (x, _) in samples.iter().take(t) {
+    for x in xs.iter().take(t) {
         tmp.mul_assign(x);
         x_prod.push(tmp);
     }
     tmp = Scalar::one();
-    for (i, (x, _)) in samples[1..].iter().enumerate().rev() {
+    for (i, x) in xs[1..].iter().enumerate().rev() {
         tmp.mul_assign(x);
         x_prod[i].mul_assign(&tmp);
     }
 
-    let mut result = C::identity();
-    for (mut l0, (x, sample)) in x_prod.into_iter().zip(&samples) {
+    let mut coefficients = Vec::with_capacity(xs.len());
+    for (mut l0, x) in x_prod.into_iter().zip(&xs) {
         // Compute the value at 0 of the Lagrange polynomial that is `0` at the other data
         // points but `1` at `x`.
         let mut denom = Scalar::one();
-        for (x0, _) in samples.iter().filter(|(x0, _)| x0 != x) {
+        for x0 in xs.iter().filter(|x0| *x0 != x) {
             let mut diff = *x0;
             diff.sub_assign(x);
             denom.mul_assign(&diff);
@@ -113,9 +451,9 @@ where
             return Err(Error::DuplicateEntry);
         }
         l0.mul_assign(inv.unwrap());
-        result.add_assign(&sample.to_affine().mul(l0));
+        coefficients.push(l0);
     }
-    Ok(result)
+    Ok(coefficients)
 }
 
 #[cfg(test)]
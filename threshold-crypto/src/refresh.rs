@@ -0,0 +1,161 @@
+use crate::{
+    encoding,
+    polynomial::{Commitment, Polynomial},
+    Error, Result, SecretKey, PK_SIZE, SK_SIZE,
+};
+use bls12_381::Scalar;
+use core::{borrow::Borrow, fmt};
+use group::ff::Field;
+use rand::RngCore;
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::AddAssign;
+
+/// A node's contribution to a proactive share refresh: a random polynomial of the key's degree
+/// whose value at `0` is zero. Adding the dealings of the agreed set of dealers to the shares
+/// changes every share but not the master key, so that shares leaked before the refresh can't be
+/// combined with those leaked after it.
+pub struct RefreshDealing {
+    polynomial: Polynomial,
+}
+
+impl RefreshDealing {
+    pub fn random(threshold: usize, mut rng: impl RngCore) -> Self {
+        let mut coefficients = vec![Scalar::zero()];
+        for _ in 0..threshold {
+            coefficients.push(Scalar::random(&mut rng));
+        }
+        Self {
+            polynomial: Polynomial::new(coefficients),
+        }
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.polynomial.degree()
+    }
+
+    /// Returns the commitment to publish, for recipients to verify their refresh shares with.
+    pub fn commitment(&self) -> RefreshCommitment {
+        RefreshCommitment(self.polynomial.commitment())
+    }
+
+    /// Returns the refresh share to send privately to the node holding the `i`-th key share.
+    pub fn refresh_share<T: Into<Scalar>>(&self, i: T) -> RefreshShare {
+        let mut x = Scalar::one();
+        x.add_assign(i.into());
+        RefreshShare(SecretKey::new(self.polynomial.evaluate(x)))
+    }
+}
+
+/// The commitment to a `RefreshDealing`, whose constant coefficient is always the identity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefreshCommitment(Commitment);
+
+impl RefreshCommitment {
+    pub fn threshold(&self) -> usize {
+        self.0.degree()
+    }
+
+    pub(crate) fn as_commitment(&self) -> &Commitment {
+        &self.0
+    }
+
+    /// Returns whether the refresh share is the `i`-th one of the committed dealing.
+    pub fn verify_refresh_share<T: Into<Scalar>>(&self, i: T, share: &RefreshShare) -> bool {
+        let mut x = Scalar::one();
+        x.add_assign(i.into());
+        self.0.verify_evaluation(x, *share.0)
+    }
+
+    /// Returns the concatenated compressed encodings of the commitment coefficients.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0
+            .coefficients()
+            .iter()
+            .flat_map(encoding::g1_to_bytes)
+            .collect()
+    }
+
+    /// Decodes the output of `to_bytes`, rejecting points outside the prime order subgroup and a
+    /// constant coefficient other than the identity, which would change the master key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.is_empty() || bytes.len() % PK_SIZE != 0 {
+            return Err(Error::InvalidLength(bytes.len()));
+        }
+        let coefficients = bytes
+            .chunks_exact(PK_SIZE)
+            .map(|chunk| {
+                encoding::g1_from_bytes(chunk.try_into().expect("chunks have the exact size"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !bool::from(coefficients[0].is_identity()) {
+            return Err(Error::NonZeroRefreshConstant);
+        }
+        Ok(Self(Commitment::new(coefficients)))
+    }
+}
+
+impl Serialize for RefreshCommitment {
+    fn serialize<S: Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        s.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for RefreshCommitment {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        let bytes: Vec<u8> = Deserialize::deserialize(d)?;
+        Self::from_bytes(&bytes).map_err(DeserializeError::custom)
+    }
+}
+
+/// The evaluation of a `RefreshDealing` for one node, to be added to its secret key share.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct RefreshShare(SecretKey);
+
+impl RefreshShare {
+    pub(crate) fn as_scalar(&self) -> &Scalar {
+        &self.0
+    }
+
+    /// Returns the canonical little-endian encoding of the refresh share. Handle with care: it is
+    /// as secret as a key share.
+    pub fn to_bytes(&self) -> [u8; SK_SIZE] {
+        self.0.to_bytes()
+    }
+
+    /// Decodes a refresh share, rejecting non-canonical encodings and zero.
+    pub fn from_bytes<B: Borrow<[u8; SK_SIZE]>>(bytes: B) -> Result<Self> {
+        SecretKey::from_bytes(bytes).map(Self)
+    }
+}
+
+/// suppress to display its value unintentionally
+impl fmt::Debug for RefreshShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RefreshShare...")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SecretKeyShares;
+    use bls12_381::G1Projective;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_refresh_rejects_nonzero_constant() {
+        let mut rng = thread_rng();
+        let pk_shares = SecretKeyShares::random(1, &mut rng).public_keys();
+        let mut coefficients = RefreshDealing::random(1, &mut rng)
+            .commitment()
+            .as_commitment()
+            .coefficients()
+            .to_vec();
+        coefficients[0] = G1Projective::generator();
+        let commitment = RefreshCommitment(Commitment::new(coefficients));
+        assert!(matches!(
+            pk_shares.refresh([commitment].iter()),
+            Err(Error::NonZeroRefreshConstant)
+        ));
+    }
+}
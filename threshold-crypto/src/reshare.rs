@@ -0,0 +1,136 @@
+use crate::{
+    encoding,
+    polynomial::{Commitment, Polynomial},
+    Error, Result, SecretKey, SecretKeyShare, PK_SIZE, SK_SIZE,
+};
+use bls12_381::Scalar;
+use core::{borrow::Borrow, fmt};
+use group::ff::Field;
+use rand::RngCore;
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::AddAssign;
+
+/// A holder's contribution to resharing the master key to a new committee: a random polynomial
+/// of the new threshold's degree whose value at `0` is the holder's secret key share.
+///
+/// Each new holder combines the reshare shares of the same `old threshold + 1` dealers, and
+/// everyone derives the new public key shares from the commitments of those same dealers, with
+/// `PublicKeyShares::combine_reshare_shares` and `PublicKeyShares::reshare` respectively.
+pub struct ReshareDealing {
+    polynomial: Polynomial,
+}
+
+impl ReshareDealing {
+    pub fn random(share: &SecretKeyShare, new_threshold: usize, mut rng: impl RngCore) -> Self {
+        let mut coefficients = vec![**share.as_secret_key()];
+        for _ in 0..new_threshold {
+            coefficients.push(Scalar::random(&mut rng));
+        }
+        Self {
+            polynomial: Polynomial::new(coefficients),
+        }
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.polynomial.degree()
+    }
+
+    /// Returns the commitment to publish, for the new holders to verify their reshare shares
+    /// with, and for everyone to check against the dealer's current public key share.
+    pub fn commitment(&self) -> ReshareCommitment {
+        ReshareCommitment(self.polynomial.commitment())
+    }
+
+    /// Returns the reshare share to send privately to the new holder of the `i`-th key share.
+    pub fn reshare_share<T: Into<Scalar>>(&self, i: T) -> ReshareShare {
+        let mut x = Scalar::one();
+        x.add_assign(i.into());
+        ReshareShare(SecretKey::new(self.polynomial.evaluate(x)))
+    }
+}
+
+/// The commitment to a `ReshareDealing`, whose constant coefficient is the dealer's public key
+/// share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReshareCommitment(Commitment);
+
+impl ReshareCommitment {
+    pub fn threshold(&self) -> usize {
+        self.0.degree()
+    }
+
+    pub(crate) fn as_commitment(&self) -> &Commitment {
+        &self.0
+    }
+
+    /// Returns whether the reshare share is the one of the new `i`-th key share.
+    pub fn verify_reshare_share<T: Into<Scalar>>(&self, i: T, share: &ReshareShare) -> bool {
+        let mut x = Scalar::one();
+        x.add_assign(i.into());
+        self.0.verify_evaluation(x, *share.0)
+    }
+
+    /// Returns the concatenated compressed encodings of the commitment coefficients.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0
+            .coefficients()
+            .iter()
+            .flat_map(encoding::g1_to_bytes)
+            .collect()
+    }
+
+    /// Decodes the output of `to_bytes`, rejecting points outside the prime order subgroup.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.is_empty() || bytes.len() % PK_SIZE != 0 {
+            return Err(Error::InvalidLength(bytes.len()));
+        }
+        let coefficients = bytes
+            .chunks_exact(PK_SIZE)
+            .map(|chunk| {
+                encoding::g1_from_bytes(chunk.try_into().expect("chunks have the exact size"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self(Commitment::new(coefficients)))
+    }
+}
+
+impl Serialize for ReshareCommitment {
+    fn serialize<S: Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        s.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for ReshareCommitment {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        let bytes: Vec<u8> = Deserialize::deserialize(d)?;
+        Self::from_bytes(&bytes).map_err(DeserializeError::custom)
+    }
+}
+
+/// The evaluation of a `ReshareDealing` for one new holder.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ReshareShare(SecretKey);
+
+impl ReshareShare {
+    pub(crate) fn as_scalar(&self) -> &Scalar {
+        &self.0
+    }
+
+    /// Returns the canonical little-endian encoding of the reshare share. Handle with care: it is
+    /// as secret as a key share.
+    pub fn to_bytes(&self) -> [u8; SK_SIZE] {
+        self.0.to_bytes()
+    }
+
+    /// Decodes a reshare share, rejecting non-canonical encodings and zero.
+    pub fn from_bytes<B: Borrow<[u8; SK_SIZE]>>(bytes: B) -> Result<Self> {
+        SecretKey::from_bytes(bytes).map(Self)
+    }
+}
+
+/// suppress to display its value unintentionally
+impl fmt::Debug for ReshareShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ReshareShare...")
+    }
+}
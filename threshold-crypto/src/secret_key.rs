@@ -3,10 +3,14 @@ mod secret_key_shares;
 pub use secret_key_share::SecretKeyShare;
 pub use secret_key_shares::SecretKeyShares;
 
-use crate::{hasher, Ciphertext, PublicKey, Signature};
+use crate::{
+    encoding, hasher, serializers, Ciphertext, HybridCiphertext, PublicKey, Result, Signature,
+    SK_SIZE,
+};
 use bls12_381::{G1Affine, Scalar};
-use core::fmt;
+use core::{borrow::Borrow, fmt};
 use group::Curve;
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
 use std::ops::{Deref, Mul};
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
@@ -18,12 +22,31 @@ impl SecretKey {
         Self(scalar)
     }
 
+    /// Returns the canonical little-endian encoding of the key. Handle with care: it is the
+    /// secret itself.
+    pub fn to_bytes(&self) -> [u8; SK_SIZE] {
+        self.0.to_bytes()
+    }
+
+    /// Decodes a key, rejecting non-canonical encodings and zero.
+    pub fn from_bytes<B: Borrow<[u8; SK_SIZE]>>(bytes: B) -> Result<Self> {
+        encoding::nonzero_scalar_from_bytes(bytes.borrow()).map(Self)
+    }
+
     pub fn sign<M: AsRef<[u8]>>(&self, msg: M) -> Signature {
         let g2 = hasher::hash(msg);
         let signature = g2.mul(&self.0);
         Signature::new(signature)
     }
 
+    /// Signs the message hashed to G2 as specified by RFC 9380, with the given domain separation
+    /// tag.
+    #[cfg(feature = "hash-to-curve")]
+    pub fn sign_with_dst<M: AsRef<[u8]>>(&self, msg: M, dst: &[u8]) -> Signature {
+        let g2 = hasher::hash_to_g2_rfc9380(msg, dst);
+        Signature::new(g2.mul(&self.0))
+    }
+
     pub fn decrypt(&self, ct: &Ciphertext) -> Option<Vec<u8>> {
         if !ct.verify() {
             return None;
@@ -34,6 +57,12 @@ impl SecretKey {
         Some(hasher::xor_with_hash(g.to_affine(), v))
     }
 
+    /// Returns `None` if the key ciphertext is invalid or the payload fails authentication.
+    pub fn decrypt_hybrid(&self, ct: &HybridCiphertext) -> Option<Vec<u8>> {
+        let key = self.decrypt(ct.as_key_ciphertext())?;
+        ct.open(key).ok()
+    }
+
     pub fn compute_public_key(&self) -> PublicKey {
         let q = G1Affine::generator();
         let projective = q.mul(&self.0);
@@ -68,3 +97,16 @@ impl Zeroize for SecretKey {
 }
 
 impl ZeroizeOnDrop for SecretKey {}
+
+impl Serialize for SecretKey {
+    fn serialize<S: Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        serializers::fixed_bytes::serialize(&self.to_bytes(), s)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretKey {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        let bytes: [u8; SK_SIZE] = serializers::fixed_bytes::deserialize(d)?;
+        Self::from_bytes(bytes).map_err(DeserializeError::custom)
+    }
+}
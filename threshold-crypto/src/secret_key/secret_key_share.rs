@@ -1,9 +1,11 @@
 use super::SecretKey;
-use crate::{Ciphertext, DecryptionShare, SignatureShare};
+use crate::{Ciphertext, DecryptionShare, RefreshShare, Result, SignatureShare, SK_SIZE};
+use core::borrow::Borrow;
 use group::Curve;
-use std::ops::Mul;
+use serde::{Deserialize, Serialize};
+use std::ops::{AddAssign, Mul};
 
-#[derive(Clone, PartialEq, Eq, Default)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Default)]
 pub struct SecretKeyShare(SecretKey);
 
 impl SecretKeyShare {
@@ -15,10 +17,45 @@ impl SecretKeyShare {
         self.0
     }
 
+    pub(crate) fn as_secret_key(&self) -> &SecretKey {
+        &self.0
+    }
+
+    /// Returns the key share refreshed with the refresh shares dealt to it. The refresh shares
+    /// must have been verified against their dealers' commitments.
+    pub fn refresh<'a, I>(&self, refresh_shares: I) -> Self
+    where
+        I: Iterator<Item = &'a RefreshShare>,
+    {
+        let mut scalar = *self.0;
+        for refresh_share in refresh_shares {
+            scalar.add_assign(refresh_share.as_scalar());
+        }
+        Self(SecretKey::new(scalar))
+    }
+
+    /// Returns the canonical little-endian encoding of the key share. Handle with care: it is the
+    /// secret itself.
+    pub fn to_bytes(&self) -> [u8; SK_SIZE] {
+        self.0.to_bytes()
+    }
+
+    /// Decodes a key share, rejecting non-canonical encodings and zero.
+    pub fn from_bytes<B: Borrow<[u8; SK_SIZE]>>(bytes: B) -> Result<Self> {
+        SecretKey::from_bytes(bytes).map(Self)
+    }
+
     pub fn sign<M: AsRef<[u8]>>(&self, msg: M) -> SignatureShare {
         SignatureShare::new(self.0.sign(msg))
     }
 
+    /// Signs the message hashed to G2 as specified by RFC 9380, with the given domain separation
+    /// tag.
+    #[cfg(feature = "hash-to-curve")]
+    pub fn sign_with_dst<M: AsRef<[u8]>>(&self, msg: M, dst: &[u8]) -> SignatureShare {
+        SignatureShare::new(self.0.sign_with_dst(msg, dst))
+    }
+
     pub fn decrypt_share(&self, ct: &Ciphertext) -> Option<DecryptionShare> {
         if !ct.verify() {
             return None;
@@ -1,2 +1,3 @@
+pub mod fixed_bytes;
 pub mod g1_projective;
 pub mod g2_projective;
@@ -0,0 +1,42 @@
+use serde::{
+    de::{Error as DeserializeError, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserializer, Serializer,
+};
+use std::fmt;
+
+pub fn serialize<S, const N: usize>(bytes: &[u8; N], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut serialize_tuple = s.serialize_tuple(N)?;
+    for byte in bytes {
+        serialize_tuple.serialize_element(byte)?;
+    }
+    serialize_tuple.end()
+}
+
+struct TupleVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for TupleVisitor<N> {
+    type Value = [u8; N];
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a tuple of size {}", N)
+    }
+    #[inline]
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let len_err = || DeserializeError::invalid_length(i, &self);
+            *byte = seq.next_element()?.ok_or_else(len_err)?;
+        }
+        Ok(bytes)
+    }
+}
+
+pub fn deserialize<'de, D, const N: usize>(d: D) -> Result<[u8; N], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    d.deserialize_tuple(N, TupleVisitor::<N>)
+}
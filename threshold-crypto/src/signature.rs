@@ -1,10 +1,12 @@
 mod signature_share;
 pub use signature_share::SignatureShare;
 
+use crate::{encoding, serializers, Result, SIG_SIZE};
 use bls12_381::{G2Affine, G2Projective};
-use core::{fmt, hash};
+use core::{borrow::Borrow, fmt, hash};
 use group::Curve;
 use hex_fmt::HexFmt;
+use serde::{de::Error as DeserializeError, Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
 
 #[derive(Clone, PartialEq, Eq)]
@@ -19,6 +21,16 @@ impl Signature {
         self.0
     }
 
+    /// Returns the compressed encoding of the signature.
+    pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
+        encoding::g2_to_bytes(&self.0)
+    }
+
+    /// Decodes a signature, rejecting points outside the prime order subgroup and the identity.
+    pub fn from_bytes<B: Borrow<[u8; SIG_SIZE]>>(bytes: B) -> Result<Self> {
+        encoding::nonidentity_g2_from_bytes(bytes.borrow()).map(Self)
+    }
+
     pub fn parity(&self) -> bool {
         let uncompressed = self.0.to_affine().to_uncompressed();
         let xor_bytes: u8 = uncompressed
@@ -68,3 +80,16 @@ impl Ord for Signature {
         }
     }
 }
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        serializers::fixed_bytes::serialize(&self.to_bytes(), s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        let bytes: [u8; SIG_SIZE] = serializers::fixed_bytes::deserialize(d)?;
+        Self::from_bytes(bytes).map_err(DeserializeError::custom)
+    }
+}
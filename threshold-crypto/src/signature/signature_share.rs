@@ -1,15 +1,28 @@
 use super::Signature;
-use core::fmt;
+use crate::{Result, SIG_SIZE};
+use core::{borrow::Borrow, fmt};
 use group::Curve;
 use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct SignatureShare(Signature);
 
 impl SignatureShare {
     pub(crate) fn new(signature: Signature) -> Self {
         Self(signature)
     }
+
+    /// Returns the compressed encoding of the signature share.
+    pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
+        self.0.to_bytes()
+    }
+
+    /// Decodes a signature share, rejecting points outside the prime order subgroup and the
+    /// identity.
+    pub fn from_bytes<B: Borrow<[u8; SIG_SIZE]>>(bytes: B) -> Result<Self> {
+        Signature::from_bytes(bytes).map(Self)
+    }
 }
 
 impl fmt::Debug for SignatureShare {
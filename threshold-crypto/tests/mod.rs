@@ -1,7 +1,12 @@
-use bls12_381::Scalar;
-use group::ff::Field;
-use rand::thread_rng;
-use threshold_crypto::{Ciphertext, DecryptionShare, SecretKey, SecretKeyShares, SignatureShare};
+use bls12_381::{pairing, G1Affine, G2Affine, G2Projective, Scalar};
+use group::{ff::Field, Curve};
+use rand::{thread_rng, Rng};
+use threshold_crypto::{
+    hasher, Ciphertext, DecryptionShare, Error, HybridCiphertext, Keystore, PublicKey,
+    PublicKeyShare, PublicKeyShares, RefreshCommitment, RefreshDealing, RefreshShare,
+    ReshareCommitment, ReshareDealing, ReshareShare, ScryptParams, SecretKey, SecretKeyShare,
+    SecretKeyShares, Signature, SignatureShare, CIPHERTEXT_VERSION,
+};
 
 fn gen_random_secret() -> SecretKey {
     let mut rnd = thread_rng();
@@ -83,6 +88,274 @@ fn test_threshold_sig() {
     assert_eq!(sig, sig2);
 }
 
+#[cfg(feature = "hash-to-curve")]
+#[test]
+fn test_threshold_sig_with_dst() {
+    let sk_shares = gen_random_secret_key_shares(1);
+    let pk_shares = sk_shares.public_keys();
+    let msg = "Totally real news";
+    let dst = b"TEST-V01-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+    let other_dst = b"OTHER-V01-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+    let sigs: Vec<(u64, SignatureShare)> = [3u64, 5u64]
+        .iter()
+        .map(|&i| (i, sk_shares.secret_key_share(i).sign_with_dst(msg, dst)))
+        .collect();
+    for (i, sig) in &sigs {
+        let pk_share = pk_shares.public_key_share(*i);
+        assert!(pk_share.verify_with_dst(sig, msg, dst));
+        assert!(!pk_share.verify_with_dst(sig, msg, other_dst)); // Wrong tag.
+    }
+    let sig = pk_shares
+        .combine_signatures(sigs.iter().map(|(i, sig)| (*i, sig)))
+        .expect("signatures match");
+    assert!(pk_shares.public_key().verify_with_dst(&sig, msg, dst));
+    assert!(!pk_shares.public_key().verify(&sig, msg)); // Default hash.
+}
+
+#[test]
+fn test_verify_secret_key_share() {
+    let sk_shares = gen_random_secret_key_shares(3);
+    let pk_shares = sk_shares.public_keys();
+    let other_pk_shares = gen_random_secret_key_shares(3).public_keys();
+    for i in 0u64..5 {
+        let sk_share = sk_shares.secret_key_share(i);
+        assert!(pk_shares.verify_secret_key_share(i, &sk_share));
+        assert!(!pk_shares.verify_secret_key_share(i + 1, &sk_share)); // Wrong index.
+        assert!(!other_pk_shares.verify_secret_key_share(i, &sk_share)); // Wrong commitment.
+    }
+
+    // A corrupted share is caught too.
+    let mut bytes = sk_shares.secret_key_share(2u64).to_bytes();
+    bytes[0] ^= 1;
+    let corrupted = SecretKeyShare::from_bytes(bytes).unwrap();
+    assert!(!pk_shares.verify_secret_key_share(2u64, &corrupted));
+}
+
+#[test]
+fn test_refresh() {
+    let mut rng = thread_rng();
+    let threshold = 2;
+    let sk_shares = gen_random_secret_key_shares(threshold);
+    let pk_shares = sk_shares.public_keys();
+
+    // Every node deals a refresh, and all nodes apply the same dealings.
+    let dealings: Vec<RefreshDealing> = (0..4)
+        .map(|_| RefreshDealing::random(threshold, &mut rng))
+        .collect();
+    let commitments: Vec<RefreshCommitment> = dealings.iter().map(|d| d.commitment()).collect();
+    let new_pk_shares = pk_shares.refresh(commitments.iter()).unwrap();
+    assert_eq!(pk_shares.public_key(), new_pk_shares.public_key());
+
+    let new_sk_shares: Vec<SecretKeyShare> = (0u64..4)
+        .map(|i| {
+            let refresh_shares: Vec<RefreshShare> =
+                dealings.iter().map(|d| d.refresh_share(i)).collect();
+            for (commitment, refresh_share) in commitments.iter().zip(&refresh_shares) {
+                assert!(commitment.verify_refresh_share(i, refresh_share));
+                assert!(!commitment.verify_refresh_share(i + 1, refresh_share));
+            }
+            let old_sk_share = sk_shares.secret_key_share(i);
+            let new_sk_share = old_sk_share.refresh(refresh_shares.iter());
+            assert!(old_sk_share != new_sk_share);
+            assert_ne!(
+                pk_shares.public_key_share(i),
+                new_pk_shares.public_key_share(i)
+            );
+            assert!(new_pk_shares.verify_secret_key_share(i, &new_sk_share));
+            assert!(!pk_shares.verify_secret_key_share(i, &new_sk_share));
+            new_sk_share
+        })
+        .collect();
+
+    // The refreshed shares still sign for the same master key.
+    let msg = b"Totally real news";
+    let hash = hasher::hash(msg);
+    let mut sig_shares: Vec<(u64, SignatureShare)> = (0u64..)
+        .zip(&new_sk_shares)
+        .take(threshold + 1)
+        .map(|(i, sk_share)| (i, sk_share.sign(msg)))
+        .collect();
+    let sig = new_pk_shares
+        .combine_signatures(sig_shares.iter().map(|(i, sig)| (*i, sig)))
+        .unwrap();
+    assert!(pk_shares.public_key().verify(&sig, msg));
+
+    // Old and new shares don't combine.
+    sig_shares[0].1 = sk_shares.secret_key_share(0u64).sign(msg);
+    let mixed_sig = new_pk_shares
+        .combine_signatures(sig_shares.iter().map(|(i, sig)| (*i, sig)))
+        .unwrap();
+    assert!(!pk_shares.public_key().verify(&mixed_sig, msg));
+    assert_eq!(
+        vec![0],
+        new_pk_shares.verify_signature_shares(sig_shares.iter().map(|(i, sig)| (*i, sig)), hash)
+    );
+
+    // Dealings of another degree are rejected, as are commitments that change the master key.
+    let wrong_degree = RefreshDealing::random(threshold + 1, &mut rng).commitment();
+    assert!(matches!(
+        pk_shares.refresh(std::iter::once(&wrong_degree)),
+        Err(Error::ThresholdMismatch { .. })
+    ));
+    let bytes = commitments[0].to_bytes();
+    assert_eq!(
+        commitments[0],
+        RefreshCommitment::from_bytes(&bytes).unwrap()
+    );
+    let pk_bytes = pk_shares.to_bytes();
+    assert!(matches!(
+        RefreshCommitment::from_bytes(&pk_bytes),
+        Err(Error::NonZeroRefreshConstant)
+    ));
+}
+
+#[test]
+fn test_reshare() {
+    let mut rng = thread_rng();
+    let (old_threshold, new_threshold) = (1, 2);
+    let sk_shares = gen_random_secret_key_shares(old_threshold);
+    let pk_shares = sk_shares.public_keys();
+
+    // Old holders 1 and 3 deal to a new committee of 7.
+    let dealers = [1u64, 3];
+    let dealings: Vec<ReshareDealing> = dealers
+        .iter()
+        .map(|&i| ReshareDealing::random(&sk_shares.secret_key_share(i), new_threshold, &mut rng))
+        .collect();
+    let commitments: Vec<(u64, ReshareCommitment)> = dealers
+        .iter()
+        .zip(&dealings)
+        .map(|(&i, dealing)| (i, dealing.commitment()))
+        .collect();
+    for (i, commitment) in &commitments {
+        assert!(pk_shares.verify_reshare_commitment(*i, commitment));
+        assert!(!pk_shares.verify_reshare_commitment(*i + 1, commitment));
+    }
+    let new_pk_shares = pk_shares
+        .reshare(commitments.iter().map(|(i, c)| (*i, c)))
+        .unwrap();
+    assert_eq!(new_threshold, new_pk_shares.threshold());
+    assert_eq!(pk_shares.public_key(), new_pk_shares.public_key());
+
+    let new_sk_shares: Vec<SecretKeyShare> = (0u64..7)
+        .map(|j| {
+            let reshare_shares: Vec<(u64, ReshareShare)> = dealers
+                .iter()
+                .zip(&dealings)
+                .map(|(&i, dealing)| (i, dealing.reshare_share(j)))
+                .collect();
+            for ((_, commitment), (_, share)) in commitments.iter().zip(&reshare_shares) {
+                assert!(commitment.verify_reshare_share(j, share));
+            }
+            let sk_share = pk_shares
+                .combine_reshare_shares(reshare_shares.iter().map(|(i, s)| (*i, s)))
+                .unwrap();
+            assert!(new_pk_shares.verify_secret_key_share(j, &sk_share));
+            sk_share
+        })
+        .collect();
+
+    // Any new threshold + 1 holders sign for the same master key.
+    let msg = b"Totally real news";
+    let sig_shares: Vec<(u64, SignatureShare)> = (0u64..)
+        .zip(&new_sk_shares)
+        .skip(4)
+        .map(|(j, sk_share)| (j, sk_share.sign(msg)))
+        .collect();
+    let sig = new_pk_shares
+        .combine_signatures(sig_shares.iter().map(|(j, sig)| (*j, sig)))
+        .unwrap();
+    assert!(pk_shares.public_key().verify(&sig, msg));
+
+    // Too few dealers, or a dealing that isn't of the dealer's key share, are rejected.
+    assert!(matches!(
+        pk_shares.reshare(commitments.iter().take(1).map(|(i, c)| (*i, c))),
+        Err(Error::NotEnoughShares)
+    ));
+    assert!(matches!(
+        pk_shares.reshare(commitments.iter().map(|(i, c)| (*i + 1, c))),
+        Err(Error::InvalidReshareCommitment(2))
+    ));
+    let bytes = commitments[0].1.to_bytes();
+    assert_eq!(
+        commitments[0].1,
+        ReshareCommitment::from_bytes(&bytes).unwrap()
+    );
+}
+
+#[test]
+fn test_keystore() {
+    let mut rng = thread_rng();
+    // Cheap parameters, to keep the test fast.
+    let params = ScryptParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+    let sk_shares = gen_random_secret_key_shares(2);
+    let pk_shares = sk_shares.public_keys();
+    let sk_share = sk_shares.secret_key_share(3u64);
+    let password = b"correct horse battery staple";
+    let keystore =
+        Keystore::encrypt_with_params_and_rng(&mut rng, params, &sk_share, 3, &pk_shares, password)
+            .unwrap();
+
+    let path = std::env::temp_dir().join(format!("keystore-{}.json", rng.gen::<u64>()));
+    keystore.save(&path).unwrap();
+    assert!(matches!(keystore.save(&path), Err(Error::Io(_)))); // Never overwritten.
+    let loaded = Keystore::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(keystore, loaded);
+    assert_eq!(3, loaded.index());
+    assert!(sk_share == loaded.decrypt(password, &pk_shares).unwrap());
+
+    // Wrong passwords and other public key shares are rejected.
+    assert!(matches!(
+        loaded.decrypt(b"wrong password", &pk_shares),
+        Err(Error::InvalidTag)
+    ));
+    let other_pk_shares = gen_random_secret_key_shares(2).public_keys();
+    assert!(matches!(
+        loaded.decrypt(password, &other_pk_shares),
+        Err(Error::KeystoreMismatch)
+    ));
+
+    // The index is authenticated.
+    let mut json: serde_json::Value = serde_json::to_value(&keystore).unwrap();
+    json["index"] = 4.into();
+    let tampered: Keystore = serde_json::from_value(json).unwrap();
+    assert!(matches!(
+        tampered.decrypt(password, &pk_shares),
+        Err(Error::InvalidTag)
+    ));
+
+    // A share that doesn't match the commitment isn't saved.
+    assert!(matches!(
+        Keystore::encrypt_with_params_and_rng(&mut rng, params, &sk_share, 4, &pk_shares, password),
+        Err(Error::InvalidSecretKeyShare)
+    ));
+
+    // Costly scrypt parameters are rejected before deriving the key.
+    for (log_n, r, p) in [(21, 8, 1), (4, 9, 1), (4, 8, 2)] {
+        let mut json: serde_json::Value = serde_json::to_value(&keystore).unwrap();
+        json["crypto"]["kdf_params"] = serde_json::json!({ "log_n": log_n, "r": r, "p": p });
+        let costly: Keystore = serde_json::from_value(json).unwrap();
+        assert!(matches!(
+            costly.decrypt(password, &pk_shares),
+            Err(Error::ScryptParamsTooLarge)
+        ));
+    }
+    let params = ScryptParams {
+        log_n: 21,
+        ..params
+    };
+    assert!(matches!(
+        Keystore::encrypt_with_params_and_rng(&mut rng, params, &sk_share, 3, &pk_shares, password),
+        Err(Error::ScryptParamsTooLarge)
+    ));
+}
+
 #[test]
 fn test_simple_enc() {
     let sk_bob = gen_random_secret();
@@ -146,3 +419,308 @@ fn test_threshold_enc() {
         .expect("decryption shares must match");
     assert_eq!(msg[..], decrypted[..]);
 }
+
+#[test]
+fn test_labeled_enc() {
+    let sk_shares = gen_random_secret_key_shares(1);
+    let pk_shares = sk_shares.public_keys();
+    let msg = b"Totally real news";
+    let ciphertext = pk_shares
+        .public_key()
+        .encrypt_with_label(&msg[..], b"epoch 5".to_vec());
+    assert!(ciphertext.verify());
+    assert_eq!(b"epoch 5", ciphertext.as_label());
+
+    let sk_share = sk_shares.secret_key_share(2u64);
+    let pk_share = pk_shares.public_key_share(2u64);
+    let share = sk_share
+        .decrypt_share(&ciphertext)
+        .expect("ciphertext must be valid");
+    assert!(pk_share.verify_decryption_share(&share, &ciphertext));
+    let decrypted = sk_shares.secret_key().decrypt(&ciphertext);
+    assert_eq!(Some(&msg[..]), decrypted.as_deref());
+
+    // Neither relabeled nor unlabeled copies are valid, nor are the decryption shares for them.
+    let (u, v, w) = (ciphertext.as_g1(), ciphertext.as_msg(), ciphertext.as_g2());
+    let relabeled = Ciphertext::new_with_label(*u, v.to_vec(), *w, b"epoch 9".to_vec());
+    let unlabeled = Ciphertext::new(*u, v.to_vec(), *w);
+    for fake_ciphertext in [relabeled, unlabeled] {
+        assert!(!fake_ciphertext.verify());
+        assert!(sk_share.decrypt_share(&fake_ciphertext).is_none());
+        assert!(!pk_share.verify_decryption_share(&share, &fake_ciphertext));
+        assert_eq!(None, sk_shares.secret_key().decrypt(&fake_ciphertext));
+    }
+}
+
+#[test]
+fn test_unlabeled_enc_is_compatible() {
+    let sk = gen_random_secret();
+    let msg = b"Totally real news";
+    let ciphertext = sk.compute_public_key().encrypt(&msg[..]);
+    assert!(ciphertext.as_label().is_empty());
+
+    // W is hashed as it was before ciphertexts had a label.
+    let (u, v, w) = (ciphertext.as_g1(), ciphertext.as_msg(), ciphertext.as_g2());
+    let hash = hasher::hash_with_g1(u.to_affine(), v);
+    assert_eq!(
+        pairing(&G1Affine::generator(), &w.to_affine()),
+        pairing(&u.to_affine(), &hash)
+    );
+    let rebuilt = Ciphertext::new(*u, v.to_vec(), *w);
+    assert!(rebuilt.verify());
+    assert_eq!(Some(&msg[..]), sk.decrypt(&rebuilt).as_deref());
+
+    // The serialized ciphertext starts with its version, and other versions are rejected.
+    let mut bytes = bincode::serialize(&ciphertext).unwrap();
+    assert_eq!(CIPHERTEXT_VERSION, bytes[0]);
+    assert_eq!(ciphertext, bincode::deserialize(&bytes).unwrap());
+    bytes[0] = 0;
+    assert!(bincode::deserialize::<Ciphertext>(&bytes).is_err());
+}
+
+#[test]
+fn test_hybrid_enc() {
+    let sk_shares = gen_random_secret_key_shares(3);
+    let pk_shares = sk_shares.public_keys();
+    let msg = vec![7u8; 1 << 16];
+    let ciphertext = pk_shares.public_key().encrypt_hybrid(&msg);
+    assert!(ciphertext.verify());
+    assert_eq!(msg.len() + 16, ciphertext.as_payload().len());
+
+    // The master key decrypts the message.
+    let decrypted = sk_shares.secret_key().decrypt_hybrid(&ciphertext);
+    assert_eq!(Some(&msg), decrypted.as_ref());
+
+    // So do 4 decryption shares of the symmetric key.
+    let shares: Vec<(u64, DecryptionShare)> = [5u64, 8u64, 7u64, 10u64]
+        .iter()
+        .map(|&i| {
+            let sk_share = sk_shares.secret_key_share(i);
+            let share = sk_share
+                .decrypt_share(ciphertext.as_key_ciphertext())
+                .expect("key ciphertext must be valid");
+            (i, share)
+        })
+        .collect();
+    let decrypted = pk_shares
+        .decrypt_hybrid(shares.iter().map(|(i, share)| (*i, share)), &ciphertext)
+        .expect("decryption shares must match");
+    assert_eq!(msg, decrypted);
+
+    // A tampered payload fails authentication.
+    let mut bytes = bincode::serialize(&ciphertext).unwrap();
+    *bytes.last_mut().unwrap() ^= 1;
+    let tampered: HybridCiphertext = bincode::deserialize(&bytes).unwrap();
+    assert!(tampered.verify());
+    assert_eq!(None, sk_shares.secret_key().decrypt_hybrid(&tampered));
+    assert!(matches!(
+        pk_shares.decrypt_hybrid(shares.iter().map(|(i, share)| (*i, share)), &tampered),
+        Err(Error::InvalidTag)
+    ));
+}
+
+#[test]
+fn test_batch_verification() {
+    let sk_shares = gen_random_secret_key_shares(3);
+    let pk_shares = sk_shares.public_keys();
+    let msg = "Totally real news";
+    let hash = hasher::hash(msg);
+    let ciphertext = pk_shares.public_key().encrypt(msg);
+    let indices = [1u64, 2u64, 3u64, 4u64, 5u64];
+
+    let mut sigs: Vec<(u64, SignatureShare)> = indices
+        .iter()
+        .map(|&i| (i, sk_shares.secret_key_share(i).sign(msg)))
+        .collect();
+    let mut dec_shares: Vec<(u64, DecryptionShare)> = indices
+        .iter()
+        .map(|&i| {
+            let share = sk_shares
+                .secret_key_share(i)
+                .decrypt_share_force(&ciphertext);
+            (i, share)
+        })
+        .collect();
+    let verify_sigs = |sigs: &[(u64, SignatureShare)]| {
+        pk_shares.verify_signature_shares(sigs.iter().map(|(i, sig)| (*i, sig)), hash)
+    };
+    let verify_dec_shares = |shares: &[(u64, DecryptionShare)]| {
+        pk_shares.verify_decryption_shares(shares.iter().map(|(i, share)| (*i, share)), &ciphertext)
+    };
+    assert!(verify_sigs(&sigs).is_empty());
+    assert!(verify_dec_shares(&dec_shares).is_empty());
+    assert!(verify_sigs(&[]).is_empty());
+
+    // An invalid share is found among valid ones.
+    sigs[3].1 = sk_shares.secret_key_share(4u64).sign("Fake news");
+    dec_shares[3].1 = sk_shares
+        .secret_key_share(1u64)
+        .decrypt_share_force(&ciphertext);
+    assert_eq!(vec![4], verify_sigs(&sigs));
+    assert_eq!(vec![4], verify_dec_shares(&dec_shares));
+
+    // Errors cancelling each other out in a plain sum are caught by the random coefficients.
+    let offset = G2Projective::generator();
+    let sig = |i: usize| sigs[i].1.as_ref().as_ref();
+    let (sig1, sig2) = (sig(1) + offset, sig(2) - offset);
+    sigs[1].1 = SignatureShare::from_bytes(G2Affine::from(sig1).to_compressed()).unwrap();
+    sigs[2].1 = SignatureShare::from_bytes(G2Affine::from(sig2).to_compressed()).unwrap();
+    assert_eq!(vec![2, 3, 4], verify_sigs(&sigs));
+}
+
+#[test]
+fn test_robust_combination() {
+    let sk_shares = gen_random_secret_key_shares(2);
+    let pk_shares = sk_shares.public_keys();
+    let msg = "Totally real news";
+    let hash = hasher::hash(msg);
+    let ciphertext = pk_shares.public_key().encrypt(msg);
+
+    // The first share is invalid, so combining the first 3 shares would fail.
+    let sigs: Vec<(u64, SignatureShare)> = [1u64, 2u64, 3u64, 4u64]
+        .iter()
+        .map(|&i| {
+            let msg = if i == 1 { "Fake news" } else { msg };
+            (i, sk_shares.secret_key_share(i).sign(msg))
+        })
+        .collect();
+    let dec_shares: Vec<(u64, DecryptionShare)> = [1u64, 2u64, 3u64, 4u64]
+        .iter()
+        .map(|&i| {
+            let sk_share = sk_shares.secret_key_share(if i == 1 { 5u64 } else { i });
+            (i, sk_share.decrypt_share_force(&ciphertext))
+        })
+        .collect();
+    let sig_iter = || sigs.iter().map(|(i, sig)| (*i, sig));
+    let dec_share_iter = || dec_shares.iter().map(|(i, share)| (*i, share));
+
+    let (sig, invalid_indices) = pk_shares
+        .combine_signatures_robust(sig_iter(), hash)
+        .expect("enough valid signature shares");
+    assert!(pk_shares.public_key().verify(&sig, msg));
+    assert_eq!(vec![1], invalid_indices);
+    let (decrypted, invalid_indices) = pk_shares
+        .decrypt_robust(dec_share_iter(), &ciphertext)
+        .expect("enough valid decryption shares");
+    assert_eq!(msg.as_bytes(), &decrypted[..]);
+    assert_eq!(vec![1], invalid_indices);
+
+    // Without the last share, only 2 valid shares are left.
+    match pk_shares.combine_signatures_robust(sig_iter().take(3), hash) {
+        Err(Error::NotEnoughValidShares { invalid_indices }) => {
+            assert_eq!(vec![1], invalid_indices)
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match pk_shares.decrypt_robust(dec_share_iter().take(3), &ciphertext) {
+        Err(Error::NotEnoughValidShares { invalid_indices }) => {
+            assert_eq!(vec![1], invalid_indices)
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(matches!(
+        pk_shares.combine_signatures_robust(sig_iter().take(2), hash),
+        Err(Error::NotEnoughShares)
+    ));
+}
+
+#[test]
+fn test_byte_encodings_round_trip() {
+    let sk_shares = gen_random_secret_key_shares(3);
+    let pk_shares = sk_shares.public_keys();
+    let sk = sk_shares.secret_key();
+    let pk = pk_shares.public_key();
+    let sk_share = sk_shares.secret_key_share(2u64);
+    let pk_share = pk_shares.public_key_share(2u64);
+    let sig = sk.sign("msg");
+    let sig_share = sk_share.sign("msg");
+    let dec_share = sk_share.decrypt_share_force(&pk.encrypt(b"msg"));
+
+    assert_eq!(sk, SecretKey::from_bytes(sk.to_bytes()).unwrap());
+    assert_eq!(pk, PublicKey::from_bytes(pk.to_bytes()).unwrap());
+    // secret keys don't implement `Debug`.
+    assert!(sk_share == SecretKeyShare::from_bytes(sk_share.to_bytes()).unwrap());
+    assert_eq!(
+        pk_share,
+        PublicKeyShare::from_bytes(pk_share.to_bytes()).unwrap()
+    );
+    assert_eq!(
+        pk_shares,
+        PublicKeyShares::from_bytes(&pk_shares.to_bytes()).unwrap()
+    );
+    assert_eq!(sig, Signature::from_bytes(sig.to_bytes()).unwrap());
+    assert_eq!(
+        sig_share,
+        SignatureShare::from_bytes(sig_share.to_bytes()).unwrap()
+    );
+    assert_eq!(
+        dec_share,
+        DecryptionShare::from_bytes(dec_share.to_bytes()).unwrap()
+    );
+
+    // serde uses the same encodings.
+    let bytes = bincode::serialize(&pk).unwrap();
+    assert_eq!(pk.to_bytes().to_vec(), bytes);
+    assert_eq!(pk, bincode::deserialize(&bytes).unwrap());
+    let bytes = bincode::serialize(&pk_shares).unwrap();
+    assert_eq!(pk_shares, bincode::deserialize(&bytes).unwrap());
+    let bytes = bincode::serialize(&sk_share).unwrap();
+    assert!(sk_share == bincode::deserialize(&bytes).unwrap());
+    let bytes = bincode::serialize(&sig_share).unwrap();
+    assert_eq!(sig_share, bincode::deserialize(&bytes).unwrap());
+    let bytes = bincode::serialize(&dec_share).unwrap();
+    assert_eq!(dec_share, bincode::deserialize(&bytes).unwrap());
+}
+
+#[test]
+fn test_invalid_encodings_are_rejected() {
+    let g1_identity = G1Affine::identity().to_compressed();
+    let g2_identity = G2Affine::identity().to_compressed();
+    assert!(matches!(
+        PublicKey::from_bytes(g1_identity),
+        Err(Error::IdentityOrZero)
+    ));
+    assert!(matches!(
+        DecryptionShare::from_bytes(g1_identity),
+        Err(Error::IdentityOrZero)
+    ));
+    assert!(matches!(
+        Signature::from_bytes(g2_identity),
+        Err(Error::IdentityOrZero)
+    ));
+    assert!(matches!(
+        SecretKey::from_bytes([0; 32]),
+        Err(Error::IdentityOrZero)
+    ));
+
+    // the modulus is not a canonical scalar.
+    let mut modulus = (-Scalar::one()).to_bytes();
+    modulus[0] += 1;
+    assert!(matches!(
+        SecretKeyShare::from_bytes(modulus),
+        Err(Error::InvalidScalar)
+    ));
+
+    // the compression flag is missing.
+    let mut uncompressed = G1Affine::generator().to_compressed();
+    uncompressed[0] &= 0x7f;
+    assert!(matches!(
+        PublicKeyShare::from_bytes(uncompressed),
+        Err(Error::InvalidPoint)
+    ));
+
+    let pk_shares = gen_random_secret_key_shares(1).public_keys();
+    let bytes = pk_shares.to_bytes();
+    assert!(matches!(
+        PublicKeyShares::from_bytes(&bytes[1..]),
+        Err(Error::InvalidLength(_))
+    ));
+    assert!(matches!(
+        PublicKeyShares::from_bytes(&[g1_identity, g1_identity].concat()),
+        Err(Error::IdentityOrZero)
+    ));
+
+    // serde checks the encodings too.
+    let bytes = bincode::serialize(&g2_identity.to_vec()).unwrap();
+    assert!(bincode::deserialize::<SignatureShare>(&bytes[8..]).is_err());
+}